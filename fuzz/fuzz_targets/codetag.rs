@@ -0,0 +1,16 @@
+#![no_main]
+
+use figfont::{FIGfont, GERMAN_CODES, REQUIRED_ASCII};
+use libfuzzer_sys::fuzz_target;
+
+// The data follows a valid font's required characters, so it's parsed as
+// codetagged characters.
+fuzz_target!(|data: &[u8]| {
+    let mut font = b"flf2a$ 1 1 2 0 0\n".to_vec();
+    for _ in 0..REQUIRED_ASCII.count() + GERMAN_CODES.len() {
+        font.extend(b"a@@\n");
+    }
+    font.extend(data);
+
+    let _ = FIGfont::read_from(&font[..]);
+});
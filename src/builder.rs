@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+
+use crate::{
+    character::FIGcharacter, error::Error, header::Header, result::Result, FIGfont, GERMAN_CODES,
+    REQUIRED_ASCII,
+};
+
+/// Assemble a FIGfont from a header and its FIGcharacters. The header can
+/// be made from scratch with Header::new or come from a parsed font.
+#[derive(Debug, Clone)]
+pub struct FontBuilder {
+    header: Header,
+    characters: HashMap<i32, FIGcharacter>,
+    allow_incomplete: bool,
+}
+
+impl FontBuilder {
+    /// Start a FIGfont with `header` and no FIGcharacters.
+    pub fn new(header: Header) -> FontBuilder {
+        FontBuilder {
+            header,
+            characters: HashMap::new(),
+            allow_incomplete: false,
+        }
+    }
+
+    /// Add the FIGcharacter for the `code` character, replacing any
+    /// previous one.
+    pub fn character(mut self, code: i32, character: FIGcharacter) -> FontBuilder {
+        self.characters.insert(code, character);
+        self
+    }
+
+    /// Build even if some of the required ASCII characters are missing.
+    /// FIGfont::get panics on a missing character if `~` is missing too.
+    pub fn allow_incomplete(mut self, allow: bool) -> FontBuilder {
+        self.allow_incomplete = allow;
+        self
+    }
+
+    /// Build the FIGfont. Fails if a FIGcharacter isn't as tall as the
+    /// header says or, unless allowed, if a required ASCII character is
+    /// missing. The characters that aren't required ASCII or German are
+    /// the codetagged ones, and the header's codetag count is set to their
+    /// number if it has one.
+    pub fn build(mut self) -> Result<FIGfont> {
+        let mut codes: Vec<i32> = self.characters.keys().copied().collect();
+        codes.sort_unstable();
+
+        for code in codes.iter() {
+            let found = self.characters[code].height();
+            if found != self.header.height() {
+                return Err(Error::HeightMismatch {
+                    expected: self.header.height(),
+                    found,
+                });
+            }
+        }
+
+        if !self.allow_incomplete {
+            let missing: Vec<i32> = REQUIRED_ASCII
+                .filter(|code| !self.characters.contains_key(code))
+                .collect();
+            if !missing.is_empty() {
+                return Err(Error::MissingCharacters(missing));
+            }
+        }
+
+        let codetags: Vec<i32> = codes
+            .into_iter()
+            .filter(|code| !REQUIRED_ASCII.contains(code) && !GERMAN_CODES.contains(code))
+            .collect();
+        if self.header.codetag_count().is_some() {
+            self.header.set_codetag_count(codetags.len() as u32);
+        }
+
+        Ok(FIGfont {
+            header: self.header,
+            characters: self.characters,
+            codetags,
+            trailer: None,
+        })
+    }
+}
@@ -1,5 +1,6 @@
 use std::{
     borrow::Cow,
+    fmt::{Display, Formatter},
     io::{BufReader, Read},
     str::from_utf8,
 };
@@ -7,15 +8,19 @@ use std::{
 use encoding::{all::ISO_8859_1, DecoderTrap, Encoding};
 
 use crate::{
-    error::{Error, ParseError},
+    error::ParseError,
     header::Header,
+    options::ParseOptions,
     result::Result,
     subcharacter::SubCharacter,
-    utils::{read_last_line, read_line},
+    utils::{read_last_line, read_line_limited},
 };
 
 /// The FIGcharacter is the representation of a single large FIGfont character.
-#[derive(Debug, Clone)]
+/// Its widths are always in terminal cells: a row is as wide as the sum of
+/// its SubCharacters' widths.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FIGcharacter {
     comment: Option<String>,
     lines: Vec<Vec<SubCharacter>>,
@@ -25,19 +30,49 @@ impl FIGcharacter {
     pub(crate) fn parse<R: Read>(
         bread: &mut BufReader<R>,
         header: &Header,
+        options: &ParseOptions,
     ) -> Result<FIGcharacter> {
-        read_character(bread, header)
+        read_character(bread, header, options)
     }
 
     pub(crate) fn parse_with_codetag<R: Read>(
         bread: &mut BufReader<R>,
         header: &Header,
+        options: &ParseOptions,
     ) -> Result<(i32, FIGcharacter)> {
-        read_character_with_codetag(bread, header)
+        read_character_with_codetag(bread, header, options)
+    }
+
+    /// Build a FIGcharacter from its rows of art. The rows are split like
+    /// the parser splits a font file's bytes, so non-ASCII text ends up as
+    /// Latin-1 SubCharacters just as if it were parsed. Every `hardblank` in
+    /// the rows becomes a SubCharacter::Blank, unless it's a space. All the
+    /// rows must have the same width.
+    pub fn from_rows(rows: &[&str], hardblank: u8) -> Result<FIGcharacter> {
+        let hardblank = match hardblank {
+            b' ' => &[][..],
+            _ => std::slice::from_ref(&hardblank),
+        };
+        let lines = rows
+            .iter()
+            .map(|row| SubCharacter::split(row.as_bytes(), hardblank))
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(ParseError::InvalidSubCharacter)?;
+
+        let mut widths = lines.iter().map(|line| row_width(line));
+        let width = widths.next().ok_or(ParseError::InvalidCharacter)?;
+        if widths.any(|w| w != width) {
+            return Err(ParseError::InvalidCharacter.into());
+        }
+
+        Ok(FIGcharacter {
+            comment: None,
+            lines,
+        })
     }
 
     /// Get the matrix of SubCharacters.
-    pub fn lines(&self) -> Cow<Vec<Vec<SubCharacter>>> {
+    pub fn lines(&self) -> Cow<'_, Vec<Vec<SubCharacter>>> {
         Cow::Borrowed(&self.lines)
     }
 
@@ -46,38 +81,202 @@ impl FIGcharacter {
         self.lines.len()
     }
 
-    /// Get the width (number of terminal cells) of FIGcharacter.
+    /// Get the width of FIGcharacter's widest row.
     pub fn width(&self) -> usize {
-        self.lines.iter().map(|x| x.len()).max().unwrap_or_default()
+        self.try_width().unwrap_or_default()
+    }
+
+    /// Get the width of FIGcharacter's widest row, or `None` if it has no
+    /// lines at all.
+    pub fn try_width(&self) -> Option<usize> {
+        self.lines.iter().map(|line| row_width(line)).max()
+    }
+
+    /// Get the width of each row of FIGcharacter, to spot the malformed rows
+    /// of a ragged FIGcharacter.
+    pub fn row_widths(&self) -> Vec<usize> {
+        self.lines.iter().map(|line| row_width(line)).collect()
+    }
+
+    /// Get the visible width of FIGcharacter: trailing spaces are not
+    /// counted, while trailing hard blanks are.
+    pub fn visible_width(&self) -> usize {
+        self.lines
+            .iter()
+            .map(|line| {
+                let len = line
+                    .iter()
+                    .rposition(|c| !is_empty_cell(c, false))
+                    .map_or(0, |i| i + 1);
+                row_width(&line[..len])
+            })
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Check if every row of FIGcharacter has the same width.
+    pub fn is_rectangular(&self) -> bool {
+        let mut widths = self.lines.iter().map(|line| row_width(line));
+        match widths.next() {
+            Some(width) => widths.all(|w| w == width),
+            None => true,
+        }
+    }
+
+    /// Check if every cell of FIGcharacter is a space or a hard blank, like
+    /// the space character usually is.
+    pub fn is_blank(&self) -> bool {
+        self.lines.iter().flatten().all(|c| is_empty_cell(c, true))
+    }
+
+    /// Count the visible cells of FIGcharacter, to compare ink density.
+    /// Hard blanks are treated as empty space if `hardblank_is_blank` is set.
+    pub fn ink_count(&self, hardblank_is_blank: bool) -> usize {
+        self.lines
+            .iter()
+            .flatten()
+            .filter(|c| !is_empty_cell(c, hardblank_is_blank))
+            .count()
+    }
+
+    /// Get the first and last visible columns of `row`, both inclusive, for
+    /// kerning. Hard blanks are treated as empty space if
+    /// `hardblank_is_blank` is set. Returns `None` if the row is blank or
+    /// doesn't exist.
+    pub fn ink_span(&self, row: usize, hardblank_is_blank: bool) -> Option<(usize, usize)> {
+        let line = self.lines.get(row)?;
+        let first = line
+            .iter()
+            .position(|c| !is_empty_cell(c, hardblank_is_blank))?;
+        let last = line
+            .iter()
+            .rposition(|c| !is_empty_cell(c, hardblank_is_blank))?;
+        Some((first, last))
+    }
+
+    /// Get a copy of FIGcharacter without the blank rows and columns around
+    /// its art. Hard blanks are treated as blank.
+    pub fn trimmed(&self) -> FIGcharacter {
+        let lines = match self.ink_bounds(true) {
+            Some((top, bottom, left, right)) => self.lines[top..=bottom]
+                .iter()
+                .map(|line| {
+                    line.iter()
+                        .skip(left)
+                        .take(right - left + 1)
+                        .cloned()
+                        .collect()
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+
+        FIGcharacter {
+            comment: self.comment.clone(),
+            lines,
+        }
+    }
+
+    /// Get a copy of FIGcharacter without the leading blank columns shared by
+    /// all of its rows, keeping the rows aligned with each other. Hard blanks
+    /// are not blank.
+    pub fn left_trimmed(&self) -> FIGcharacter {
+        let common = self
+            .lines
+            .iter()
+            .filter_map(|line| line.iter().position(|c| !is_empty_cell(c, false)))
+            .min()
+            .unwrap_or_else(|| self.width());
+
+        FIGcharacter {
+            comment: self.comment.clone(),
+            lines: self
+                .lines
+                .iter()
+                .map(|line| line.iter().skip(common).cloned().collect())
+                .collect(),
+        }
     }
 
     /// Get the comment of the FIGcharacter, if any.
     /// Only for codetagged characters.
-    pub fn comment(&self) -> Option<Cow<String>> {
+    pub fn comment(&self) -> Option<Cow<'_, String>> {
         self.comment.as_ref().map(Cow::Borrowed)
     }
+
+    /// Get the tight bounds of the visible art as
+    /// `(first_row, last_row, first_column, last_column)`, all inclusive.
+    /// Hard blanks are treated as empty space if `hardblank_is_blank` is set.
+    /// Returns `None` if the FIGcharacter is entirely blank (eg. space).
+    pub fn ink_bounds(&self, hardblank_is_blank: bool) -> Option<(usize, usize, usize, usize)> {
+        let mut bounds: Option<(usize, usize, usize, usize)> = None;
+
+        for (row, line) in self.lines.iter().enumerate() {
+            let mut inked = line
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| !is_empty_cell(c, hardblank_is_blank))
+                .map(|(col, _)| col);
+
+            if let Some(first) = inked.next() {
+                let last = inked.next_back().unwrap_or(first);
+                bounds = Some(match bounds {
+                    Some((top, _, left, right)) => (top, row, left.min(first), right.max(last)),
+                    None => (row, row, first, last),
+                });
+            }
+        }
+
+        bounds
+    }
+}
+
+impl Display for FIGcharacter {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        for (i, line) in self.lines.iter().enumerate() {
+            if i != 0 {
+                writeln!(fmt)?;
+            }
+            for c in line {
+                write!(fmt, "{}", c)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn row_width(line: &[SubCharacter]) -> usize {
+    line.iter().map(|c| c.width()).sum()
+}
+
+fn is_empty_cell(c: &SubCharacter, hardblank_is_blank: bool) -> bool {
+    match c {
+        SubCharacter::Blank => hardblank_is_blank,
+        SubCharacter::Symbol(sym) => sym == " ",
+    }
 }
 
 fn read_character_with_codetag<R: Read>(
     bread: &mut BufReader<R>,
     header: &Header,
+    options: &ParseOptions,
 ) -> Result<(i32, FIGcharacter)> {
-    let (codetag, comment) = read_codetag(bread)?;
-    let mut character = read_character(bread, header)?;
+    let (codetag, comment) = read_codetag(bread, options.max_line_length)?;
+    let mut character = read_character(bread, header, options)?;
     character.comment = comment;
 
     Ok((codetag, character))
 }
 
-fn read_codetag<R: Read>(bread: &mut BufReader<R>) -> Result<(i32, Option<String>)> {
-    let line = read_line(bread)?;
+fn read_codetag<R: Read>(
+    bread: &mut BufReader<R>,
+    max_len: usize,
+) -> Result<(i32, Option<String>)> {
+    let line = read_line_limited(bread, max_len, "line length")?;
     let mut line = line.splitn(2, |c| c == &b' ');
     let mut code = line.next().ok_or(ParseError::InvalidCharacter)?;
     let comment = match line.next() {
-        Some(bytes) => match ISO_8859_1.decode(bytes, DecoderTrap::Replace) {
-            Ok(comment) => Some(comment),
-            Err(_) => None,
-        },
+        Some(bytes) => ISO_8859_1.decode(bytes, DecoderTrap::Replace).ok(),
         None => None,
     };
 
@@ -91,7 +290,7 @@ fn read_codetag<R: Read>(bread: &mut BufReader<R>) -> Result<(i32, Option<String
     let code = if code.starts_with(b"0x") || code.starts_with(b"0X") {
         let code = from_utf8(&code[2..]).map_err(|_| ParseError::InvalidCharacter)?;
         i32::from_str_radix(code, 16)
-    } else if code.starts_with(b"0") {
+    } else if code.len() > 1 && code.starts_with(b"0") {
         let code = from_utf8(&code[1..]).map_err(|_| ParseError::InvalidCharacter)?;
         i32::from_str_radix(code, 8)
     } else {
@@ -100,15 +299,33 @@ fn read_codetag<R: Read>(bread: &mut BufReader<R>) -> Result<(i32, Option<String
             .parse()
     };
 
-    Ok((
-        code.map_err(|_| ParseError::InvalidCharacter)? * sign,
-        comment,
-    ))
+    // "--2147483648" parses to i32::MIN, which can't be negated.
+    let code = code
+        .ok()
+        .and_then(|code| code.checked_mul(sign))
+        .ok_or(ParseError::InvalidCharacter)?;
+
+    Ok((code, comment))
 }
 
-fn read_character<R: Read>(bread: &mut BufReader<R>, header: &Header) -> Result<FIGcharacter> {
-    let mut lines = read_lines(bread, header.height())?;
+fn read_character<R: Read>(
+    bread: &mut BufReader<R>,
+    header: &Header,
+    options: &ParseOptions,
+) -> Result<FIGcharacter> {
+    let height = options.height_override.unwrap_or_else(|| header.height());
+    // A FIGcharacter has at least one line, read_lines relies on it.
+    if height == 0 {
+        return Err(ParseError::InvalidHeader.into());
+    }
+    if height > options.max_height {
+        return Err(ParseError::LimitExceeded("height").into());
+    }
+    let mut lines = read_lines(bread, height, options.max_char_width)?;
 
+    // Lines still end with their delimiter here: a line holding only the
+    // delimiter is a valid zero-width row, while an empty line has no
+    // delimiter at all and is invalid.
     let first = &lines[0];
 
     if first.is_empty() {
@@ -142,33 +359,32 @@ fn read_character<R: Read>(bread: &mut BufReader<R>, header: &Header) -> Result<
         line.truncate(len - 1);
     }
 
+    let hard_blank = match header.hard_blank_char() {
+        b" " => &[][..],
+        hard_blank => hard_blank,
+    };
     let mut res: Vec<Vec<SubCharacter>> = Vec::with_capacity(lines.len());
 
     for line in lines {
         res.push(
-            SubCharacter::split(&line[..], header.hard_blank_char())
-                .ok()
-                .ok_or::<Error>(ParseError::InvalidCharacter.into())?,
+            SubCharacter::split(&line[..], hard_blank).map_err(ParseError::InvalidSubCharacter)?,
         );
     }
 
-    let max_len = res
-        .iter()
-        .map(|line| line.iter().map(|c| c.width()).sum())
-        .max()
-        .unwrap_or(0);
+    if options.pad_rows {
+        let max_len = res.iter().map(|line| row_width(line)).max().unwrap_or(0);
 
-    res = res
-        .into_iter()
-        .map(|mut line| {
-            if line.len() < max_len {
-                for _ in line.len()..max_len {
+        res = res
+            .into_iter()
+            .map(|mut line| {
+                let width = row_width(&line);
+                for _ in width..max_len {
                     line.push(SubCharacter::Symbol(" ".to_string()));
                 }
-            }
-            line
-        })
-        .collect();
+                line
+            })
+            .collect();
+    }
 
     Ok(FIGcharacter {
         comment: None,
@@ -176,14 +392,18 @@ fn read_character<R: Read>(bread: &mut BufReader<R>, header: &Header) -> Result<
     })
 }
 
-fn read_lines<R: Read>(bread: &mut BufReader<R>, num: usize) -> Result<Vec<Vec<u8>>> {
+fn read_lines<R: Read>(
+    bread: &mut BufReader<R>,
+    num: usize,
+    max_len: usize,
+) -> Result<Vec<Vec<u8>>> {
     let mut lines = Vec::with_capacity(num);
 
     for _ in 0..(num - 1) {
-        lines.push(read_line(bread)?);
+        lines.push(read_line_limited(bread, max_len, "char width")?);
     }
 
-    lines.push(read_last_line(bread)?);
+    lines.push(read_last_line(bread, max_len, "char width")?);
 
     Ok(lines)
 }
@@ -1,3 +1,4 @@
+use crate::format::FontFormat;
 #[cfg(feature = "zip")]
 use zip::result::ZipError;
 
@@ -11,6 +12,17 @@ pub enum Error {
     #[error("failed to read file")]
     #[cfg(feature = "zip")]
     Io(#[from] Io),
+    #[error("font not found, tried {tried:?}")]
+    FontNotFound { tried: Vec<std::path::PathBuf> },
+    #[error("character is {found} lines tall, but the font is {expected}")]
+    HeightMismatch { expected: usize, found: usize },
+    #[error("font is missing the required characters {0:?}")]
+    MissingCharacters(Vec<i32>),
+    #[error("font {path:?} is a {format:?} font, which can't be parsed")]
+    UnsupportedFormat {
+        path: std::path::PathBuf,
+        format: FontFormat,
+    },
 }
 
 #[cfg(feature = "zip")]
@@ -42,10 +54,18 @@ pub enum ParseError {
     NotEnoughData,
     #[error("invalid header")]
     InvalidHeader,
+    #[error("baseline {baseline} is greater than the height {height}")]
+    InvalidBaseline { baseline: usize, height: usize },
+    #[error("file ended after {read} of {expected} comment lines")]
+    UnexpectedEndOfComment { expected: usize, read: usize },
     #[error("invalid character")]
     InvalidCharacter,
+    #[error("invalid subcharacter: {0}")]
+    InvalidSubCharacter(String),
     #[error("invalid font")]
     InvalidFont,
     #[error("invalid extension")]
     InvalidExtension,
+    #[error("{0} exceeds the parse limit")]
+    LimitExceeded(&'static str),
 }
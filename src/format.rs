@@ -0,0 +1,77 @@
+use std::io::{ErrorKind, Read};
+
+use crate::{error::ParseError, header::MAGIC_NUMBER, result::Result, utils::BOM};
+
+const TOILET_MAGIC_NUMBER: &[u8] = b"tlf2a";
+const ZIP_MAGIC_NUMBER: &[u8] = b"PK\x03\x04";
+
+/// Format of a font file.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FontFormat {
+    /// A plain FIGfont (`flf2a`).
+    FIGfont,
+    /// A TOIlet font (`tlf2a`). It can't be parsed yet.
+    TOIlet,
+    /// A zip archive, it should hold a zipped FIGfont.
+    Zip,
+}
+
+/// Encoding a FIGfont's art bytes look like they were written in.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ArtEncoding {
+    /// Only ASCII bytes.
+    Ascii,
+    /// Valid UTF-8 with some non-ASCII characters. They're parsed as
+    /// Latin-1, so each of them shows up as several SubCharacters.
+    Utf8,
+    /// Bytes that aren't valid UTF-8, so the art is probably Latin-1.
+    Latin1,
+}
+
+/// Detect the format of a font from its magic number, without parsing it.
+/// A leading UTF-8 BOM and whitespace are skipped like the parser does,
+/// but only on the first line, and nothing past the magic number is read
+/// from `reader`.
+pub fn detect_format<R: Read>(mut reader: R) -> Result<FontFormat> {
+    let mut next = || -> Result<Option<u8>> {
+        let mut byte = [0u8];
+        match reader.read_exact(&mut byte) {
+            Ok(()) => Ok(Some(byte[0])),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    };
+
+    let mut byte = next()?;
+    if byte == Some(BOM[0]) {
+        for expected in &BOM[1..] {
+            if next()? != Some(*expected) {
+                return Err(ParseError::InvalidHeader.into());
+            }
+        }
+        byte = next()?;
+    }
+    while matches!(byte, Some(b) if b.is_ascii_whitespace() && b != b'\n') {
+        byte = next()?;
+    }
+
+    let mut magic = Vec::with_capacity(MAGIC_NUMBER.len());
+    while let Some(b) = byte {
+        magic.push(b);
+        if magic == ZIP_MAGIC_NUMBER {
+            return Ok(FontFormat::Zip);
+        }
+        if magic.len() == MAGIC_NUMBER.len() {
+            break;
+        }
+        byte = next()?;
+    }
+
+    if magic == MAGIC_NUMBER {
+        Ok(FontFormat::FIGfont)
+    } else if magic == TOILET_MAGIC_NUMBER {
+        Ok(FontFormat::TOIlet)
+    } else {
+        Err(ParseError::InvalidHeader.into())
+    }
+}
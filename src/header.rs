@@ -1,19 +1,21 @@
 use std::{
     borrow::Cow,
     cmp::Ordering,
-    io::{BufRead, BufReader, Read},
+    collections::HashMap,
+    io::{self, BufReader, Read},
     str::{from_utf8, FromStr},
 };
 
 use crate::{
     error::{Error, ParseError},
+    options::ParseOptions,
     result::Result,
-    utils::read_line,
+    utils::{read_line_limited, read_raw_line, skip_bom_and_whitespace},
 };
 
 use bitflags::bitflags;
 
-const MAGIC_NUMBER: &[u8] = b"flf2a";
+pub(crate) const MAGIC_NUMBER: &[u8] = b"flf2a";
 
 bitflags! {
     /// The FIGfont's layout informations.
@@ -41,7 +43,49 @@ bitflags! {
         const VERTICAL_PAIR = 2048;
         const VERTICAL_BIGX = 4096;
         const VERTICAL_KERNING = 8192;
-        const VERTICAL_SMUSH = 16392;
+        const VERTICAL_SMUSH = 16384;
+    }
+}
+
+bitflags! {
+    /// The horizontal part of a FIGfont's layout.
+    pub struct HorizontalLayout: u32 {
+        /// Equals smushing.
+        const EQUAL = 1;
+        /// Underscore smushing.
+        const LOWLINE = 2;
+        /// Hierarchy smushing.
+        const HIERARCHY = 4;
+        /// Pair brackets smushing.
+        const PAIR = 8;
+        /// Big X smushing.
+        const BIGX = 16;
+        /// Hard blank smushing.
+        const HARDBLANK = 32;
+        /// Apply kerning.
+        const KERNING = 64;
+        /// Apply smushing.
+        const SMUSH = 128;
+    }
+}
+
+bitflags! {
+    /// The vertical part of a FIGfont's layout, shifted down to bit 0.
+    pub struct VerticalLayout: u32 {
+        /// Equals smushing.
+        const EQUAL = 1;
+        /// Underscore smushing.
+        const LOWLINE = 2;
+        /// Hierarchy smushing.
+        const HIERARCHY = 4;
+        /// Horizontal line smushing.
+        const PAIR = 8;
+        /// Vertical line supersmushing.
+        const BIGX = 16;
+        /// Apply kerning.
+        const KERNING = 32;
+        /// Apply smushing.
+        const SMUSH = 64;
     }
 }
 
@@ -57,25 +101,138 @@ impl FromStr for Layout {
     }
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for Layout {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        self.bits().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Layout {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        let bits = u32::deserialize(deserializer)?;
+        Layout::from_bits(bits)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid layout bits: {}", bits)))
+    }
+}
+
 /// FIGfont's header.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Header {
     hard_blank_char: Vec<u8>,
     height: usize,
     baseline: usize,
     max_length: usize,
+    old_layout: i32,
+    full_layout: Option<Layout>,
     layout: Layout,
     comment: String,
-    print_direction: PrintDirection,
+    comment_lines: usize,
+    print_direction: Option<PrintDirection>,
     codetag_count: Option<u32>,
 }
 
 impl Header {
-    pub(crate) fn parse<R: Read>(bread: &mut BufReader<R>) -> Result<Header> {
-        parse_header(bread)
+    /// Create the header of a new FIGfont, with every header line field
+    /// present, no comment, left to right print direction and no codetags.
+    /// The old layout is derived from `layout`, universal smushing (which
+    /// it can't express) becomes 0. Like a parsed header it fails on a zero
+    /// height, a baseline past the height, or a hard blank the header line
+    /// can't hold: an empty one or one with a space or a line ending.
+    pub fn new(
+        hard_blank: &[u8],
+        height: usize,
+        baseline: usize,
+        max_length: usize,
+        layout: Layout,
+    ) -> Result<Header> {
+        let unrepresentable = |c: &u8| matches!(c, b' ' | b'\r' | b'\n');
+        if hard_blank.is_empty() || hard_blank.iter().any(unrepresentable) || height == 0 {
+            return Err(ParseError::InvalidHeader.into());
+        }
+        if baseline > height {
+            return Err(ParseError::InvalidBaseline { baseline, height }.into());
+        }
+
+        let mut header = Header {
+            hard_blank_char: hard_blank.to_vec(),
+            height,
+            baseline,
+            max_length,
+            old_layout: 0,
+            full_layout: Some(layout),
+            layout,
+            comment: String::new(),
+            comment_lines: 0,
+            print_direction: Some(PrintDirection::LeftToRight),
+            codetag_count: Some(0),
+        };
+        header.old_layout = match header.smushmode() {
+            128 => 0,
+            smushmode => smushmode,
+        };
+        Ok(header)
+    }
+
+    /// Set the font's comment, one header comment line per line of it.
+    pub fn with_comment(mut self, comment: &str) -> Header {
+        self.comment_lines = if comment.is_empty() {
+            0
+        } else {
+            comment.split('\n').count()
+        };
+        self.comment = comment.to_string();
+        self
+    }
+
+    /// Set the print direction.
+    pub fn with_print_direction(mut self, print_direction: PrintDirection) -> Header {
+        self.print_direction = Some(print_direction);
+        self
+    }
+
+    pub(crate) fn set_codetag_count(&mut self, count: u32) {
+        self.codetag_count = Some(count);
+    }
+
+    pub(crate) fn parse<R: Read>(
+        bread: &mut BufReader<R>,
+        options: &ParseOptions,
+    ) -> Result<Header> {
+        let mut header = Header::parse_fields(bread, options)?;
+        header.parse_comment(bread, options)?;
+        Ok(header)
+    }
+
+    /// Parse the header line, leaving the comment empty.
+    pub(crate) fn parse_fields<R: Read>(
+        bread: &mut BufReader<R>,
+        options: &ParseOptions,
+    ) -> Result<Header> {
+        parse_header(bread, options)
     }
 
-    /// Get the hard blank character.
+    pub(crate) fn parse_comment<R: Read>(
+        &mut self,
+        bread: &mut BufReader<R>,
+        options: &ParseOptions,
+    ) -> Result<()> {
+        self.comment = read_string_lines(bread, self.comment_lines, options.max_line_length)?;
+        Ok(())
+    }
+
+    /// Get the hard blank character. It's the whole header field following
+    /// the signature, so it can span more than one byte.
+    /// A space hard blank can't be told apart from the art's spaces, so those
+    /// fonts have no SubCharacter::Blank and every space is a plain space,
+    /// like figlet's smushing treats them.
     pub fn hard_blank_char(&self) -> &[u8] {
         &self.hard_blank_char[..]
     }
@@ -100,24 +257,134 @@ impl Header {
         self.layout
     }
 
-    /// Get the font's comment.
-    pub fn comment(&self) -> Cow<str> {
+    /// Get the horizontal part of the font's layout.
+    pub fn horizontal_layout(&self) -> HorizontalLayout {
+        HorizontalLayout::from_bits_truncate(self.layout.bits() & 0xff)
+    }
+
+    /// Get the vertical part of the font's layout.
+    pub fn vertical_layout(&self) -> VerticalLayout {
+        VerticalLayout::from_bits_truncate(self.layout.bits() >> 8)
+    }
+
+    /// Get the font's comment. It's read by the header's line count, so it
+    /// can contain anything, even lines that look like FIGcharacter rows.
+    pub fn comment(&self) -> Cow<'_, str> {
         Cow::Borrowed(&self.comment)
     }
 
-    /// Get the print direction.
+    /// Get the `Key: Value` lines of the font's comment as a map, with both
+    /// sides trimmed. Lines without a key or a value are skipped, and so are
+    /// bare URLs like `http://figlet.org`, whose "value" starts with `//`; if
+    /// a key is repeated, the last value wins.
+    pub fn metadata(&self) -> HashMap<String, String> {
+        self.comment
+            .lines()
+            .filter_map(|line| line.split_once(':'))
+            .map(|(key, value)| (key.trim(), value.trim()))
+            .filter(|(key, value)| !key.is_empty() && !value.is_empty() && !value.starts_with("//"))
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    /// Get the print direction. It comes only from the header's print
+    /// direction field and defaults to left to right when the field is
+    /// missing; no full layout bit affects it, so there's nothing to
+    /// reconcile.
     pub fn print_direction(&self) -> PrintDirection {
-        self.print_direction
+        self.print_direction.unwrap_or(PrintDirection::LeftToRight)
     }
 
     /// Get the number of codetagged characters.
     pub fn codetag_count(&self) -> Option<u32> {
         self.codetag_count
     }
+
+    /// Get the horizontal layout mode the font asks for by default.
+    /// The full layout (which is derived from the old layout when missing)
+    /// decides: the smush bit means smushing, the kerning bit means kerning
+    /// and neither means full width. Smushing wins when both are set.
+    pub fn default_layout_mode(&self) -> LayoutMode {
+        if self.layout.contains(Layout::HORIZONTAL_SMUSH) {
+            LayoutMode::Smushing
+        } else if self.layout.contains(Layout::HORIZONTAL_KERNING) {
+            LayoutMode::Kerning
+        } else {
+            LayoutMode::FullWidth
+        }
+    }
+
+    /// Get the values of the header line, in file order.
+    pub fn raw_fields(&self) -> RawFields {
+        RawFields {
+            hard_blank: self.hard_blank_char.clone(),
+            height: self.height,
+            baseline: self.baseline,
+            max_length: self.max_length,
+            old_layout: self.old_layout,
+            comment_lines: self.comment_lines,
+            print_direction: self.print_direction,
+            full_layout: self.full_layout,
+            codetag_count: self.codetag_count,
+        }
+    }
+
+    /// Get the horizontal layout as the old layout number figlet's `-m`
+    /// takes: -1 for full width, 0 for kerning, the smushing rules for
+    /// controlled smushing and 128 for universal smushing.
+    pub fn smushmode(&self) -> i32 {
+        match self.default_layout_mode() {
+            LayoutMode::FullWidth => -1,
+            LayoutMode::Kerning => 0,
+            LayoutMode::Smushing => match self.layout.bits() & 63 {
+                0 => 128,
+                rules => rules as i32,
+            },
+        }
+    }
+}
+
+/// Typographic metrics of a FIGfont.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Metrics {
+    /// Height (lines) of every FIGcharacter.
+    pub height: usize,
+    /// Lines from the top of a FIGcharacter to its baseline.
+    pub baseline: usize,
+    /// Max length of a FIGcharacter's line in the font file.
+    pub max_length: usize,
+}
+
+/// The fields of a FIGfont's header line, in file order. The optional ones
+/// are `None` when the header line doesn't have them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawFields {
+    pub hard_blank: Vec<u8>,
+    pub height: usize,
+    pub baseline: usize,
+    pub max_length: usize,
+    pub old_layout: i32,
+    pub comment_lines: usize,
+    pub print_direction: Option<PrintDirection>,
+    pub full_layout: Option<Layout>,
+    pub codetag_count: Option<u32>,
+}
+
+/// Horizontal layout mode.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LayoutMode {
+    /// Characters are printed at their full width.
+    FullWidth,
+    /// Characters are moved together until they touch.
+    Kerning,
+    /// Characters are moved together and overlapped by one column, using
+    /// the layout's smushing rules.
+    Smushing,
 }
 
 /// Print direction enum.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PrintDirection {
     LeftToRight,
     RightToLeft,
@@ -138,22 +405,34 @@ impl FromStr for PrintDirection {
     }
 }
 
-fn read_string_lines<R: Read>(bread: &mut BufReader<R>, num: usize) -> Result<String> {
-    let mut lines = String::new();
-
-    for _ in 0..num {
-        bread.read_line(&mut lines)?;
-    }
+fn read_string_lines<R: Read>(
+    bread: &mut BufReader<R>,
+    num: usize,
+    max_len: usize,
+) -> Result<String> {
+    let mut lines = Vec::new();
+
+    for read in 0..num {
+        let line = read_raw_line(bread, max_len, "line length")?;
+        let line = match line.strip_suffix(b"\n") {
+            Some(line) => line,
+            None => {
+                return Err(ParseError::UnexpectedEndOfComment {
+                    expected: num,
+                    read,
+                }
+                .into())
+            }
+        };
 
-    if lines.ends_with("\r\n") {
-        lines.truncate(lines.len() - 2);
-    } else if lines.ends_with("\n") {
-        lines.truncate(lines.len() - 1);
-    } else {
-        return Err(ParseError::NotEnoughData.into());
+        // CRLF fonts get the same comment as LF ones.
+        if read != 0 {
+            lines.push(b'\n');
+        }
+        lines.extend(line.strip_suffix(b"\r").unwrap_or(line));
     }
 
-    Ok(lines)
+    String::from_utf8(lines).map_err(|e| Error::from(io::Error::new(io::ErrorKind::InvalidData, e)))
 }
 
 macro_rules! parse {
@@ -174,21 +453,27 @@ macro_rules! parse {
     };
 }
 
-fn parse_header<R: Read>(bread: &mut BufReader<R>) -> Result<Header> {
-    let header = read_line(bread)?;
+fn parse_header<R: Read>(bread: &mut BufReader<R>, options: &ParseOptions) -> Result<Header> {
+    let header = read_line_limited(bread, options.max_line_length, "line length")?;
+    let header = skip_bom_and_whitespace(&header);
     let header: Vec<u8> = if header.starts_with(MAGIC_NUMBER) {
-        header.into_iter().skip(MAGIC_NUMBER.len()).collect()
+        header[MAGIC_NUMBER.len()..].to_vec()
     } else {
         return Err(ParseError::InvalidHeader.into());
     };
 
-    let arguments: Vec<&[u8]> = header
+    let mut arguments: Vec<&[u8]> = header
         .split(|c| c == &b' ')
         .enumerate()
         .filter(|(i, x)| *i == 0 || !x.is_empty())
         .map(|(_, x)| x)
         .collect();
 
+    // A space hard blank leaves the signature followed by two spaces.
+    if header.starts_with(b"  ") {
+        arguments[0] = b" ";
+    }
+
     if arguments.len() < 6 || arguments.len() > 9 {
         return Err(ParseError::InvalidHeader.into());
     }
@@ -200,21 +485,33 @@ fn parse_header<R: Read>(bread: &mut BufReader<R>) -> Result<Header> {
     let mut hard_blank_char: Vec<u8> = Vec::with_capacity(arguments[0].len());
     hard_blank_char.extend_from_slice(arguments[0]);
     let height: usize = parse!(arguments[1]).ok_or(ParseError::InvalidHeader)?;
+    if height == 0 {
+        return Err(ParseError::InvalidHeader.into());
+    }
     let baseline: usize = parse!(arguments[2]).ok_or(ParseError::InvalidHeader)?;
     let max_length: usize = parse!(arguments[3]).ok_or(ParseError::InvalidHeader)?;
     let old_layout: i32 = parse!(arguments[4]).ok_or(ParseError::InvalidHeader)?;
 
-    let print_direction: PrintDirection = if arguments.len() > 6 {
-        parse!(arguments[6]).ok_or(ParseError::InvalidHeader)?
+    if baseline > height {
+        return Err(ParseError::InvalidBaseline { baseline, height }.into());
+    }
+
+    // The optional fields are positional: a font only has a full layout if
+    // it has a print direction, and a codetag count if it has both. A full
+    // layout written in place of the print direction is rejected, as it's
+    // neither 0 nor 1.
+    let print_direction: Option<PrintDirection> = if arguments.len() > 6 {
+        Some(parse!(arguments[6]).ok_or(ParseError::InvalidHeader)?)
     } else {
-        PrintDirection::LeftToRight
+        None
     };
 
-    let layout: Layout = if arguments.len() > 7 {
-        parse!(arguments[7]).ok_or(ParseError::InvalidHeader)?
+    let full_layout: Option<Layout> = if arguments.len() > 7 {
+        Some(parse!(arguments[7]).ok_or(ParseError::InvalidHeader)?)
     } else {
-        full_layout_from_old_layout(old_layout)
+        None
     };
+    let layout = full_layout.unwrap_or_else(|| full_layout_from_old_layout(old_layout));
 
     let codetag_count: Option<u32> = if arguments.len() > 8 {
         Some(parse!(arguments[8]).ok_or(ParseError::InvalidHeader)?)
@@ -222,18 +519,21 @@ fn parse_header<R: Read>(bread: &mut BufReader<R>) -> Result<Header> {
         None
     };
 
-    let comment: String = {
-        let comment_lines: usize = parse!(arguments[5]).ok_or(ParseError::InvalidHeader)?;
-        read_string_lines(bread, comment_lines)?
-    };
+    let comment_lines: usize = parse!(arguments[5]).ok_or(ParseError::InvalidHeader)?;
+    if comment_lines > options.max_comment_lines {
+        return Err(ParseError::LimitExceeded("comment lines").into());
+    }
 
     Ok(Header {
         hard_blank_char,
         height,
         baseline,
         max_length,
-        comment,
+        old_layout,
+        comment: String::new(),
+        comment_lines,
         print_direction,
+        full_layout,
         layout,
         codetag_count,
     })
@@ -243,7 +543,7 @@ fn full_layout_from_old_layout(old_layout: i32) -> Layout {
     let raw = match old_layout.cmp(&0) {
         Ordering::Equal => 64,
         Ordering::Less => 0,
-        Ordering::Greater => (old_layout as u32 & 31) | 128,
+        Ordering::Greater => (old_layout as u32 & 63) | 128,
     };
 
     Layout::from_bits_truncate(raw)
@@ -1,46 +1,122 @@
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufRead, BufReader, Read};
+use std::ops::RangeInclusive;
 use std::path::Path;
 
-use error::ParseError;
+use error::{Error, ParseError};
 
+pub mod builder;
 pub mod character;
 pub mod error;
+pub mod format;
 pub mod header;
+pub mod options;
 pub mod result;
+#[cfg(feature = "metrics")]
+pub mod stats;
+#[cfg(not(feature = "metrics"))]
+mod stats;
 pub mod subcharacter;
+#[cfg(feature = "testing")]
+pub mod testing;
 mod utils;
 
 use crate::result::Result;
+use crate::stats::{Phase, Recorder};
+use crate::utils::Fnv1a;
 
-const DEUTSCH_CODE_POINTS: [i32; 7] = [196, 214, 220, 228, 246, 252, 223];
+/// Codes of the ASCII characters every FIGfont defines, in file order.
+pub const REQUIRED_ASCII: RangeInclusive<i32> = 32..=126;
+
+/// Codes of the German characters every FIGfont defines after the ASCII
+/// ones, in file order: Ä Ö Ü ä ö ü ß.
+pub const GERMAN_CODES: [i32; 7] = [196, 214, 220, 228, 246, 252, 223];
 
 const STANDARD_FONT: &[u8] = include_bytes!("../fonts/plain/standard.flf");
 
 pub use crate::{
+    builder::FontBuilder,
     character::FIGcharacter,
-    header::{Header, Layout, PrintDirection},
+    format::{detect_format, ArtEncoding, FontFormat},
+    header::{
+        Header, HorizontalLayout, Layout, LayoutMode, Metrics, PrintDirection, RawFields,
+        VerticalLayout,
+    },
+    options::ParseOptions,
     subcharacter::SubCharacter,
 };
 
+#[cfg(feature = "metrics")]
+pub use crate::stats::{ParseStats, PhaseStats};
+
 /// FIGfont reader and parser.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FIGfont {
     header: Header,
     characters: HashMap<i32, FIGcharacter>,
+    codetags: Vec<i32>,
+    trailer: Option<Vec<u8>>,
 }
 
 impl FIGfont {
     /// Read and parse a FIGfont from a path. It can be zipped if you have zip
     /// feature enabled.
     pub fn load_from<P: AsRef<Path>>(path: P) -> Result<FIGfont> {
-        load_from(path)
+        load_from(path, &Default::default())
+    }
+
+    /// Read and parse a FIGfont from a path with custom ParseOptions.
+    pub fn load_from_with_options<P: AsRef<Path>>(
+        path: P,
+        options: &ParseOptions,
+    ) -> Result<FIGfont> {
+        load_from(path, options)
+    }
+
+    /// Find a FIGfont by name in `dir` like figlet does, trying `name.flf`,
+    /// `name.tlf` then, if you have zip feature enabled, `name.flf.zip`. A
+    /// `name.tlf` that isn't a plain FIGfont fails with
+    /// Error::UnsupportedFormat, since TOIlet fonts can't be parsed yet.
+    pub fn load_by_name<P: AsRef<Path>>(name: &str, dir: P) -> Result<FIGfont> {
+        load_by_name(name, dir.as_ref(), &Default::default())
     }
 
-    /// Read and parse a FIGfont from a impl Read.
+    /// Read and parse a FIGfont from a impl Read. The reader is consumed
+    /// sequentially and doesn't need to implement Seek, so it can be a
+    /// network stream.
     pub fn read_from<R: Read>(reader: R) -> Result<FIGfont> {
-        parse(reader)
+        parse(reader, &Default::default())
+    }
+
+    /// Read and parse a FIGfont from a impl Read with custom ParseOptions.
+    pub fn read_from_with_options<R: Read>(reader: R, options: &ParseOptions) -> Result<FIGfont> {
+        parse(reader, options)
+    }
+
+    /// Read and parse a FIGfont from a impl Read with custom ParseOptions,
+    /// recording how long each parse phase took and how many bytes it
+    /// consumed.
+    #[cfg(feature = "metrics")]
+    pub fn read_from_with_stats<R: Read>(
+        reader: R,
+        options: &ParseOptions,
+    ) -> Result<(FIGfont, ParseStats)> {
+        let mut recorder = Recorder::new();
+        let font = parse_recording(reader, options, &mut recorder, &mut |_, _| {})?;
+        Ok((font, recorder.finish()))
+    }
+
+    /// Read and parse a FIGfont from a impl Read with custom ParseOptions,
+    /// calling `on_progress` after every FIGcharacter with the number parsed
+    /// so far and, if the header has a codetag count, the total.
+    pub fn read_from_with_progress<R: Read, F: FnMut(usize, Option<usize>)>(
+        reader: R,
+        options: &ParseOptions,
+        mut on_progress: F,
+    ) -> Result<FIGfont> {
+        parse_recording(reader, options, &mut Recorder::new(), &mut on_progress)
     }
 
     /// Get the standard FIGfont. (hardcoded)
@@ -53,75 +129,391 @@ impl FIGfont {
         &self.header
     }
 
+    /// Get the current FIGfont's typographic metrics.
+    pub fn metrics(&self) -> Metrics {
+        Metrics {
+            height: self.header.height(),
+            baseline: self.header.baseline(),
+            max_length: self.header.max_length(),
+        }
+    }
+
+    /// Get the number of codetagged characters that were actually parsed.
+    pub fn actual_codetag_count(&self) -> u32 {
+        self.codetags.len() as u32
+    }
+
+    /// Get the data found after the last FIGcharacter, if any. Only
+    /// captured when parsing with ParseOptions::capture_trailer.
+    pub fn trailer(&self) -> Option<&[u8]> {
+        self.trailer.as_deref()
+    }
+
+    /// Get the codes of the codetagged characters that look like a parse
+    /// desync: the non-negative ones not greater than the non-negative
+    /// codetag before them, the negative ones not less than the negative
+    /// codetag before them, and the ones past the last Unicode code point.
+    /// They're in file order.
+    pub fn suspicious_codetags(&self) -> Vec<i32> {
+        let mut previous = None;
+        let mut previous_negative = None;
+        self.codetags
+            .iter()
+            .copied()
+            .filter(|&code| {
+                if code < 0 {
+                    let suspicious = previous_negative.is_some_and(|previous| code >= previous);
+                    previous_negative = Some(code);
+                    suspicious
+                } else {
+                    let suspicious =
+                        previous.is_some_and(|previous| code <= previous) || code > 0x10FFFF;
+                    previous = Some(code);
+                    suspicious
+                }
+            })
+            .collect()
+    }
+
     /// Get the FIGcharacter for the `code` character.
     pub fn get(&self, code: i32) -> &FIGcharacter {
         self.characters
             .get(&code)
             .unwrap_or_else(|| self.characters.get(&126i32).unwrap())
     }
+
+    /// Check if every FIGcharacter is as tall as the header says. A mismatch
+    /// means the font was parsed with ParseOptions::height_override or that
+    /// the characters went out of sync.
+    pub fn height_consistency(&self) -> bool {
+        self.inconsistent_heights().is_empty()
+    }
+
+    /// Get the sorted codes of the FIGcharacters whose height doesn't match
+    /// the header's.
+    pub fn inconsistent_heights(&self) -> Vec<i32> {
+        let mut codes: Vec<i32> = self
+            .characters
+            .iter()
+            .filter(|(_, character)| character.height() != self.header.height())
+            .map(|(code, _)| *code)
+            .collect();
+        codes.sort_unstable();
+        codes
+    }
+
+    /// Get a preview of the FIGcharacter for `c`: its art trimmed of the blank
+    /// rows and columns around it, with hard blanks printed as spaces.
+    /// Returns `None` if the FIGfont doesn't define `c`.
+    pub fn preview(&self, c: char) -> Option<String> {
+        self.characters
+            .get(&(c as i32))
+            .map(|character| character.trimmed().to_string())
+    }
+
+    /// Get the printable ASCII characters this FIGfont defines, in order,
+    /// each with its FIGcharacter's art, for a type sample.
+    pub fn render_all_printable(&self) -> Vec<(char, String)> {
+        REQUIRED_ASCII
+            .filter_map(|code| {
+                self.characters
+                    .get(&code)
+                    .map(|character| (code as u8 as char, character.to_string()))
+            })
+            .collect()
+    }
+
+    /// Get a hash of the header and of every FIGcharacter, for cache keys.
+    /// Fonts parsed from the same bytes hash the same. The hash is FNV-1a
+    /// over a fixed encoding of the font, so it's the same on every platform
+    /// and Rust version and can be persisted.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = Fnv1a::new();
+        let raw = self.header.raw_fields();
+        hasher.write_bytes(&raw.hard_blank);
+        hasher.write_u64(raw.height as u64);
+        hasher.write_u64(raw.baseline as u64);
+        hasher.write_u64(raw.max_length as u64);
+        hasher.write(&raw.old_layout.to_le_bytes());
+        hasher.write_u64(raw.comment_lines as u64);
+        match raw.print_direction {
+            None => hasher.write(&[0]),
+            Some(PrintDirection::LeftToRight) => hasher.write(&[1]),
+            Some(PrintDirection::RightToLeft) => hasher.write(&[2]),
+        }
+        match raw.full_layout {
+            None => hasher.write(&[0]),
+            Some(layout) => {
+                hasher.write(&[1]);
+                hasher.write(&layout.bits().to_le_bytes());
+            }
+        }
+        match raw.codetag_count {
+            None => hasher.write(&[0]),
+            Some(count) => {
+                hasher.write(&[1]);
+                hasher.write(&count.to_le_bytes());
+            }
+        }
+        hasher.write_bytes(self.header.comment().as_bytes());
+
+        let mut codes: Vec<&i32> = self.characters.keys().collect();
+        codes.sort_unstable();
+        hasher.write_u64(codes.len() as u64);
+        for code in codes {
+            let character = &self.characters[code];
+            hasher.write(&code.to_le_bytes());
+            match character.comment() {
+                None => hasher.write(&[0]),
+                Some(comment) => {
+                    hasher.write(&[1]);
+                    hasher.write_bytes(comment.as_bytes());
+                }
+            }
+            let lines = character.lines();
+            hasher.write_u64(lines.len() as u64);
+            for line in lines.iter() {
+                hasher.write_u64(line.len() as u64);
+                for sub in line {
+                    match sub {
+                        SubCharacter::Blank => hasher.write(&[0]),
+                        SubCharacter::Symbol(symbol) => {
+                            hasher.write(&[1]);
+                            hasher.write_bytes(symbol.as_bytes());
+                        }
+                    }
+                }
+            }
+        }
+        hasher.finish()
+    }
+
+    /// Get the sorted codes of the FIGcharacters that contain `sub`, eg.
+    /// SubCharacter::Blank for the ones using hard blanks.
+    pub fn chars_containing(&self, sub: &SubCharacter) -> Vec<i32> {
+        let mut codes: Vec<i32> = self
+            .characters
+            .iter()
+            .filter(|(_, character)| character.lines().iter().flatten().any(|c| c == sub))
+            .map(|(code, _)| *code)
+            .collect();
+        codes.sort_unstable();
+        codes
+    }
+
+    /// Guess the encoding the art was written in from its bytes. Every row
+    /// is checked on its own, hard blanks included.
+    pub fn art_encoding(&self) -> ArtEncoding {
+        let mut encoding = ArtEncoding::Ascii;
+
+        for character in self.characters.values() {
+            for line in character.lines().iter() {
+                let mut bytes = Vec::new();
+                for sub in line {
+                    match sub {
+                        SubCharacter::Blank => bytes.extend(self.header.hard_blank_char()),
+                        SubCharacter::Symbol(sym) => {
+                            for c in sym.chars() {
+                                push_art_byte(&mut bytes, c);
+                            }
+                        }
+                    }
+                }
+
+                if bytes.is_ascii() {
+                    continue;
+                } else if std::str::from_utf8(&bytes).is_err() {
+                    return ArtEncoding::Latin1;
+                }
+                encoding = ArtEncoding::Utf8;
+            }
+        }
+
+        encoding
+    }
+
+    /// Write every FIGcharacter to `dir`, creating it if needed, as a text
+    /// file named after its code (eg. `65.txt`) with hard blanks printed as
+    /// spaces.
+    pub fn dump_chars<P: AsRef<Path>>(&self, dir: P) -> Result<()> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+
+        for (code, character) in self.characters.iter() {
+            std::fs::write(
+                dir.join(format!("{}.txt", code)),
+                format!("{}\n", character),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Compare the characters defined by this FIGfont and `other`. Returns
+    /// the sorted codes only in this FIGfont and the sorted codes only in
+    /// `other`.
+    pub fn char_set_diff(&self, other: &FIGfont) -> (Vec<i32>, Vec<i32>) {
+        let only_in = |a: &FIGfont, b: &FIGfont| {
+            let mut codes: Vec<i32> = a
+                .characters
+                .keys()
+                .filter(|code| !b.characters.contains_key(code))
+                .copied()
+                .collect();
+            codes.sort_unstable();
+            codes
+        };
+
+        (only_in(self, other), only_in(other, self))
+    }
+
+    /// Replace the FIGcharacter for the `code` character, or add it if it's
+    /// missing. The FIGcharacter must be as tall as the font. A new code
+    /// outside the required ASCII and German ones is added as a codetag,
+    /// and counted in the header's codetag count if it has one.
+    pub fn set_char(&mut self, code: i32, character: FIGcharacter) -> Result<()> {
+        if character.height() != self.header.height() {
+            return Err(Error::HeightMismatch {
+                expected: self.header.height(),
+                found: character.height(),
+            });
+        }
+
+        let is_codetag = !REQUIRED_ASCII.contains(&code) && !GERMAN_CODES.contains(&code);
+        if self.characters.insert(code, character).is_none() && is_codetag {
+            self.codetags.push(code);
+            if self.header.codetag_count().is_some() {
+                self.header.set_codetag_count(self.actual_codetag_count());
+            }
+        }
+        Ok(())
+    }
 }
 
-fn parse<R: Read>(reader: R) -> Result<FIGfont> {
-    let mut bread /* mlmlmlml */ = BufReader::new(reader);
+/// Push the byte `c` was decoded from as Latin-1. Chars past U+00FF can't
+/// come from a font file (they can from serde), so they're pushed as UTF-8.
+fn push_art_byte(bytes: &mut Vec<u8>, c: char) {
+    if (c as u32) <= 0xFF {
+        bytes.push(c as u8);
+    } else {
+        bytes.extend(c.encode_utf8(&mut [0; 4]).as_bytes());
+    }
+}
 
-    let header = Header::parse(&mut bread)?;
+/// Read and parse only the header of a FIGfont, comment included, leaving the
+/// characters alone.
+pub fn validate_header<R: Read>(reader: R) -> Result<Header> {
+    Header::parse(&mut BufReader::new(reader), &Default::default())
+}
 
+fn parse<R: Read>(reader: R, options: &ParseOptions) -> Result<FIGfont> {
+    parse_recording(reader, options, &mut Recorder::new(), &mut |_, _| {})
+}
+
+fn parse_recording<R: Read>(
+    reader: R,
+    options: &ParseOptions,
+    recorder: &mut Recorder,
+    on_progress: &mut dyn FnMut(usize, Option<usize>),
+) -> Result<FIGfont> {
+    let mut bread /* mlmlmlml */ = BufReader::new(Recorder::wrap(reader));
+
+    let mut header = Header::parse_fields(&mut bread, options)?;
+    recorder.record(&bread, Phase::Header);
+    header.parse_comment(&mut bread, options)?;
+    recorder.record(&bread, Phase::Comment);
+
+    let required = REQUIRED_ASCII.count() + GERMAN_CODES.len();
+    let total = header
+        .codetag_count()
+        .map(|count| required + count as usize);
     let mut characters = HashMap::new();
 
-    for codepoint in 32..127 {
-        characters.insert(codepoint, FIGcharacter::parse(&mut bread, &header)?);
+    for codepoint in REQUIRED_ASCII {
+        characters.insert(
+            codepoint,
+            FIGcharacter::parse(&mut bread, &header, options)?,
+        );
+        on_progress(characters.len(), total);
     }
 
-    for codepoint in DEUTSCH_CODE_POINTS.iter() {
+    for codepoint in GERMAN_CODES.iter() {
         let codepoint = *codepoint;
 
-        characters.insert(codepoint, FIGcharacter::parse(&mut bread, &header)?);
+        characters.insert(
+            codepoint,
+            FIGcharacter::parse(&mut bread, &header, options)?,
+        );
+        on_progress(characters.len(), total);
     }
 
-    let mut cnt = 0;
-    while !bread.fill_buf()?.is_empty() {
-        let (codepoint, character) = FIGcharacter::parse_with_codetag(&mut bread, &header)?;
-        characters.insert(codepoint, character);
-        cnt += 1;
+    recorder.record(&bread, Phase::Characters);
+
+    let mut codetags = Vec::new();
+    let mut trailer = None;
+    if options.capture_trailer {
+        let mut rest = Vec::new();
+        bread.read_to_end(&mut rest)?;
+
+        let mut bread = BufReader::new(&rest[..]);
+        let mut start = 0;
+        while start < rest.len() && header.codetag_count() != Some(codetags.len() as u32) {
+            match FIGcharacter::parse_with_codetag(&mut bread, &header, options) {
+                Ok((codepoint, character)) => {
+                    characters.insert(codepoint, character);
+                    codetags.push(codepoint);
+                    on_progress(required + codetags.len(), total);
+                    start = rest.len() - bread.get_ref().len() - bread.buffer().len();
+                }
+                Err(_) => break,
+            }
+        }
+
+        if start < rest.len() {
+            trailer = Some(rest[start..].to_vec());
+        }
+    } else {
+        while !bread.fill_buf()?.is_empty() {
+            let (codepoint, character) =
+                FIGcharacter::parse_with_codetag(&mut bread, &header, options)?;
+            characters.insert(codepoint, character);
+            codetags.push(codepoint);
+            on_progress(required + codetags.len(), total);
+        }
     }
 
+    recorder.record(&bread, Phase::Codetags);
+
     if let Some(expected_cnt) = header.codetag_count() {
-        if expected_cnt != cnt {
+        if expected_cnt as usize != codetags.len() {
             return Err(ParseError::InvalidFont.into());
         }
     }
 
-    Ok(FIGfont { header, characters })
+    Ok(FIGfont {
+        header,
+        characters,
+        codetags,
+        trailer,
+    })
 }
 
 #[cfg(feature = "zip")]
-fn load_from_zip<P: AsRef<Path>>(path: P) -> Result<FIGfont> {
-    use crate::error::Error;
+fn load_from_zip(path: &Path, file_name: &str, options: &ParseOptions) -> Result<FIGfont> {
     use zip::ZipArchive;
 
-    let mut zip = ZipArchive::new(File::open(path.as_ref())?)?;
-
-    let file_name = path
-        .as_ref()
-        .file_name()
-        .ok_or::<Error>(ParseError::InvalidFont.into())?
-        .to_str()
-        .ok_or::<Error>(ParseError::InvalidFont.into())?;
+    let mut zip = ZipArchive::new(File::open(path)?)?;
 
     let f = zip.by_name(file_name)?;
 
-    parse(f)
+    parse(f, options)
 }
 
 #[cfg(feature = "zip")]
 fn is_plain<P: AsRef<Path>>(path: P) -> Result<bool> {
-    let mut f = File::open(path)?;
-    let mut number: [u8; 5] = [0; 5];
-    f.read_exact(&mut number)?;
-    Ok(&number == b"flf2a")
+    Ok(detect_format(File::open(path)?)? == FontFormat::FIGfont)
 }
 
-fn load_from<P: AsRef<Path>>(path: P) -> Result<FIGfont> {
+fn load_from<P: AsRef<Path>>(path: P, options: &ParseOptions) -> Result<FIGfont> {
     let path = path.as_ref();
     match path.extension() {
         Some(ext) => {
@@ -137,24 +529,981 @@ fn load_from<P: AsRef<Path>>(path: P) -> Result<FIGfont> {
     #[cfg(feature = "zip")]
     {
         if is_plain(path)? {
-            parse(File::open(path)?)
+            parse(File::open(path)?, options)
         } else {
-            load_from_zip(path)
+            let file_name = path
+                .file_name()
+                .ok_or::<Error>(ParseError::InvalidFont.into())?
+                .to_str()
+                .ok_or::<Error>(ParseError::InvalidFont.into())?;
+
+            load_from_zip(path, file_name, options)
         }
     }
 
     #[cfg(not(feature = "zip"))]
     {
-        parse(File::open(path)?)
+        parse(File::open(path)?, options)
+    }
+}
+
+fn load_by_name(name: &str, dir: &Path, options: &ParseOptions) -> Result<FIGfont> {
+    let mut tried = Vec::new();
+
+    let path = dir.join(format!("{}.flf", name));
+    if path.is_file() {
+        return load_from(path, options);
+    }
+    tried.push(path);
+
+    let path = dir.join(format!("{}.tlf", name));
+    if path.is_file() {
+        return match detect_format(File::open(&path)?)? {
+            FontFormat::FIGfont => parse(File::open(path)?, options),
+            format => Err(Error::UnsupportedFormat { path, format }),
+        };
+    }
+    tried.push(path);
+
+    #[cfg(feature = "zip")]
+    {
+        let path = dir.join(format!("{}.flf.zip", name));
+        if path.is_file() {
+            return load_from_zip(&path, &format!("{}.flf", name), options);
+        }
+        tried.push(path);
     }
+
+    Err(Error::FontNotFound { tried })
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::FIGfont;
+    use crate::{
+        detect_format,
+        error::{Error, ParseError},
+        validate_header, ArtEncoding, FIGcharacter, FIGfont, FontBuilder, FontFormat, Header,
+        HorizontalLayout, Layout, LayoutMode, Metrics, ParseOptions, PrintDirection, RawFields,
+        SubCharacter, VerticalLayout, REQUIRED_ASCII,
+    };
+
+    /// Build a font from a header (comment included), the space character and
+    /// a character used for every other required code.
+    fn font_bytes(header: &str, space: &str, character: &str) -> Vec<u8> {
+        let mut font = header.as_bytes().to_vec();
+        font.extend(space.as_bytes());
+        font.extend(character.repeat(101).as_bytes());
+        font
+    }
+
+    /// A directory under the system's temp dir, unique to this test run and
+    /// removed when dropped.
+    struct ScratchDir(std::path::PathBuf);
+
+    impl ScratchDir {
+        fn new(name: &str) -> ScratchDir {
+            let dir = std::env::temp_dir().join(format!("figfont-{}-{}", name, std::process::id()));
+            let _ = std::fs::remove_dir_all(&dir);
+            std::fs::create_dir_all(&dir).unwrap();
+            ScratchDir(dir)
+        }
+    }
+
+    impl Drop for ScratchDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
 
     #[test]
     fn default() {
         assert!(FIGfont::standard().is_ok());
     }
+
+    #[test]
+    fn height_consistency() {
+        let font = FIGfont::standard().unwrap();
+        assert!(font.height_consistency());
+        assert!(font.inconsistent_heights().is_empty());
+    }
+
+    #[test]
+    fn font_format() {
+        let mut reader = &b"flf2a$ 6 5 16 15 11"[..];
+        assert_eq!(detect_format(&mut reader).unwrap(), FontFormat::FIGfont);
+        assert_eq!(reader, b"$ 6 5 16 15 11");
+
+        assert_eq!(
+            detect_format(&b"tlf2a$ 6 5 16 15 11"[..]).unwrap(),
+            FontFormat::TOIlet
+        );
+        assert_eq!(
+            detect_format(&include_bytes!("../fonts/zipped/standard.flf")[..]).unwrap(),
+            FontFormat::Zip
+        );
+        assert!(detect_format(&b"flf"[..]).is_err());
+        assert!(detect_format(&b"hello world"[..]).is_err());
+    }
+
+    #[test]
+    fn bom() {
+        let font = font_bytes(
+            "\u{FEFF}flf2a$ 1 1 2 0 1\r\ncomment\r\n",
+            "$@@\r\n",
+            "a@@\r\n",
+        );
+        assert_eq!(detect_format(&font[..]).unwrap(), FontFormat::FIGfont);
+        let font = FIGfont::read_from(&font[..]).unwrap();
+        assert_eq!(font.header().hard_blank_char(), b"$");
+
+        let font = font_bytes(" \tflf2a$ 1 1 2 0 1\ncomment\n", "$@@\n", "a@@\n");
+        assert_eq!(detect_format(&font[..]).unwrap(), FontFormat::FIGfont);
+        assert!(FIGfont::read_from(&font[..]).is_ok());
+
+        assert!(detect_format(&b"\xEF\xBBflf2a"[..]).is_err());
+
+        let font = font_bytes("\nflf2a$ 1 1 2 0 1\ncomment\n", "$@@\n", "a@@\n");
+        assert!(detect_format(&font[..]).is_err());
+        assert!(FIGfont::read_from(&font[..]).is_err());
+    }
+
+    #[test]
+    fn crlf() {
+        let lf = font_bytes(
+            "flf2a$ 2 1 3 0 2 0 64 1\nline 1\nline 2\n",
+            "$@\n$@@\n",
+            "a@\na@@\n",
+        );
+        let mut crlf = Vec::new();
+        for &b in lf.iter().chain(b"300\nb@\nb@@\n") {
+            if b == b'\n' {
+                crlf.push(b'\r');
+            }
+            crlf.push(b);
+        }
+
+        let mut lf = lf;
+        lf.extend(b"300\nb@\nb@@\n");
+        let lf = FIGfont::read_from(&lf[..]).unwrap();
+        assert_eq!(FIGfont::read_from(&crlf[..]).unwrap(), lf);
+        assert_eq!(lf.header().comment(), "line 1\nline 2");
+    }
+
+    #[test]
+    fn comment_with_delimiters() {
+        let font = font_bytes("flf2a$ 1 1 2 0 2\ncomment\n@@\n", "a@@\n", "a@@\n");
+        let font = FIGfont::read_from(&font[..]).unwrap();
+        assert_eq!(font.header().comment(), "comment\n@@");
+        assert_eq!(font.get(32).to_string(), "a");
+    }
+
+    #[test]
+    fn zero_height() {
+        let font = font_bytes("flf2a$ 0 0 2 0 1\ncomment\n", "a@@\n", "a@@\n");
+        assert!(matches!(
+            FIGfont::read_from(&font[..]),
+            Err(Error::Parse(ParseError::InvalidHeader))
+        ));
+
+        let font = font_bytes("flf2a$ 1 1 2 0 1\ncomment\n", "a@@\n", "a@@\n");
+        let options = ParseOptions::new().height_override(Some(0));
+        assert!(matches!(
+            FIGfont::read_from_with_options(&font[..], &options),
+            Err(Error::Parse(ParseError::InvalidHeader))
+        ));
+    }
+
+    #[test]
+    fn header_only() {
+        let header = validate_header(&b"flf2a$ 6 5 16 15 1\ncomment\n"[..]).unwrap();
+        assert_eq!(header.height(), 6);
+        assert_eq!(header.comment(), "comment");
+
+        assert_eq!(
+            validate_header(crate::STANDARD_FONT).unwrap(),
+            *FIGfont::standard().unwrap().header()
+        );
+        assert!(validate_header(&b"flf2a$ 6 5 16 15 2\ncomment\n"[..]).is_err());
+    }
+
+    #[test]
+    fn non_seekable_reader() {
+        struct Trickle<'a>(&'a [u8]);
+
+        impl std::io::Read for Trickle<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                let len = buf.len().min(self.0.len()).min(1);
+                buf[..len].copy_from_slice(&self.0[..len]);
+                self.0 = &self.0[len..];
+                Ok(len)
+            }
+        }
+
+        assert!(FIGfont::read_from(Trickle(crate::STANDARD_FONT)).is_ok());
+    }
+
+    #[test]
+    fn load_by_name() {
+        assert!(FIGfont::load_by_name("standard", "fonts/plain").is_ok());
+
+        match FIGfont::load_by_name("missing", "fonts/plain") {
+            Err(Error::FontNotFound { tried }) => {
+                assert_eq!(tried[0], std::path::Path::new("fonts/plain/missing.flf"));
+                assert_eq!(tried[1], std::path::Path::new("fonts/plain/missing.tlf"));
+                assert_eq!(tried.len(), if cfg!(feature = "zip") { 3 } else { 2 });
+            }
+            _ => panic!("expected FontNotFound"),
+        }
+
+        let dir = ScratchDir::new("load-by-name-tlf");
+        std::fs::write(
+            dir.0.join("toilet.tlf"),
+            "tlf2a$ 1 1 2 0 0
+",
+        )
+        .unwrap();
+        assert!(matches!(
+            FIGfont::load_by_name("toilet", &dir.0),
+            Err(Error::UnsupportedFormat {
+                format: FontFormat::TOIlet,
+                ..
+            })
+        ));
+        std::fs::copy("fonts/plain/standard.flf", dir.0.join("standard.tlf")).unwrap();
+        assert_eq!(
+            FIGfont::load_by_name("standard", &dir.0).unwrap(),
+            FIGfont::standard().unwrap()
+        );
+    }
+
+    #[cfg(feature = "zip")]
+    #[test]
+    fn load_by_name_zip() {
+        let dir = ScratchDir::new("load-by-name");
+        std::fs::copy("fonts/zipped/standard.flf", dir.0.join("standard.flf.zip")).unwrap();
+
+        assert_eq!(
+            FIGfont::load_by_name("standard", &dir.0).unwrap(),
+            FIGfont::standard().unwrap()
+        );
+    }
+
+    #[test]
+    fn dump_chars() {
+        let scratch = ScratchDir::new("dump-chars");
+        let dir = scratch.0.join("chars");
+        let font = FIGfont::standard().unwrap();
+        font.dump_chars(&dir).unwrap();
+
+        assert_eq!(
+            std::fs::read_dir(&dir).unwrap().count(),
+            font.characters.len()
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.join("124.txt")).unwrap(),
+            format!("{}\n", font.get('|' as i32))
+        );
+    }
+
+    #[test]
+    fn ink_bounds() {
+        let font = FIGfont::standard().unwrap();
+
+        assert_eq!(font.get(32).ink_bounds(true), None);
+        assert_eq!(font.get(32).ink_bounds(false), Some((0, 5, 1, 1)));
+        assert_eq!(font.get('|' as i32).ink_bounds(true), Some((0, 5, 1, 3)));
+    }
+
+    #[test]
+    fn ink_span() {
+        let character = FIGcharacter::from_rows(&[" /$ ", "    "], b'$').unwrap();
+        assert_eq!(character.ink_span(0, true), Some((1, 1)));
+        assert_eq!(character.ink_span(0, false), Some((1, 2)));
+        assert_eq!(character.ink_span(1, false), None);
+        assert_eq!(character.ink_span(2, false), None);
+    }
+
+    #[test]
+    fn preview() {
+        let font = FIGfont::standard().unwrap();
+
+        assert_eq!(font.preview('|').unwrap(), " _ \n| |\n| |\n| |\n| |\n|_|");
+        assert_eq!(font.preview(' ').unwrap(), "");
+        assert_eq!(font.preview('\u{2603}'), None);
+    }
+
+    #[test]
+    fn render_all_printable() {
+        let font = FIGfont::standard().unwrap();
+        let printable = font.render_all_printable();
+
+        assert_eq!(printable.len(), 95);
+        assert_eq!(printable[0].0, ' ');
+        assert_eq!(printable[94].0, '~');
+        assert_eq!(printable[92].1, font.get('|' as i32).to_string());
+    }
+
+    #[test]
+    fn default_layout_mode() {
+        let font = FIGfont::standard().unwrap();
+        assert_eq!(font.header().default_layout_mode(), LayoutMode::Smushing);
+
+        let mode = |layout: &str| {
+            let header = format!("flf2a$ 1 1 2 {}\ncomment\n", layout);
+            FIGfont::read_from(&font_bytes(&header, "a@@\n", "a@@\n")[..])
+                .unwrap()
+                .header()
+                .default_layout_mode()
+        };
+        assert_eq!(mode("-1 1"), LayoutMode::FullWidth);
+        assert_eq!(mode("0 1"), LayoutMode::Kerning);
+        assert_eq!(mode("15 1"), LayoutMode::Smushing);
+        assert_eq!(mode("15 1 0 64"), LayoutMode::Kerning);
+        assert_eq!(mode("15 1 0 192"), LayoutMode::Smushing);
+        assert_eq!(mode("15 1 0 15"), LayoutMode::FullWidth);
+    }
+
+    #[test]
+    fn smushmode() {
+        assert_eq!(FIGfont::standard().unwrap().header().smushmode(), 15);
+
+        let smushmode = |layout: &str| {
+            let header = format!("flf2a$ 1 1 2 {}\ncomment\n", layout);
+            FIGfont::read_from(&font_bytes(&header, "a@@\n", "a@@\n")[..])
+                .unwrap()
+                .header()
+                .smushmode()
+        };
+        assert_eq!(smushmode("-1 1"), -1);
+        assert_eq!(smushmode("0 1"), 0);
+        assert_eq!(smushmode("15 1"), 15);
+        assert_eq!(smushmode("63 1"), 63);
+        assert_eq!(smushmode("15 1 0 64"), 0);
+        assert_eq!(smushmode("15 1 0 128"), 128);
+        assert_eq!(smushmode("15 1 0 15"), -1);
+    }
+
+    #[test]
+    fn comment_lines() {
+        let font = font_bytes("flf2a$ 1 1 2 0 0\n", "a@@\n", "a@@\n");
+        assert_eq!(
+            FIGfont::read_from(&font[..]).unwrap().header().comment(),
+            ""
+        );
+
+        let result = FIGfont::read_from(&b"flf2a$ 1 1 2 0 3\ncomment\n"[..]);
+        assert!(matches!(
+            result,
+            Err(Error::Parse(ParseError::UnexpectedEndOfComment {
+                expected: 3,
+                read: 1
+            }))
+        ));
+    }
+
+    #[test]
+    fn metadata() {
+        let header = "flf2a$ 1 1 2 0 6\nMy font\nAuthor: Jane Doe\nVersion:1.0 \nNote:\n\
+                      http://figlet.org\nHomepage: https://example.com\n";
+        let font = FIGfont::read_from(&font_bytes(header, "a@@\n", "a@@\n")[..]).unwrap();
+        let metadata = font.header().metadata();
+
+        assert_eq!(metadata.len(), 3);
+        assert_eq!(metadata["Author"], "Jane Doe");
+        assert_eq!(metadata["Version"], "1.0");
+        assert_eq!(metadata["Homepage"], "https://example.com");
+    }
+
+    #[test]
+    fn split_layout() {
+        let font = FIGfont::standard().unwrap();
+        let header = font.header();
+
+        assert_eq!(
+            header.horizontal_layout(),
+            HorizontalLayout::SMUSH
+                | HorizontalLayout::EQUAL
+                | HorizontalLayout::LOWLINE
+                | HorizontalLayout::HIERARCHY
+                | HorizontalLayout::PAIR
+        );
+        assert_eq!(
+            header.vertical_layout(),
+            VerticalLayout::SMUSH
+                | VerticalLayout::EQUAL
+                | VerticalLayout::LOWLINE
+                | VerticalLayout::HIERARCHY
+                | VerticalLayout::PAIR
+                | VerticalLayout::BIGX
+        );
+
+        let header = "flf2a$ 1 1 2 -1 1 0 16384\ncomment\n";
+        let font = FIGfont::read_from(&font_bytes(header, "a@@\n", "a@@\n")[..]).unwrap();
+        assert_eq!(font.header().horizontal_layout(), HorizontalLayout::empty());
+        assert_eq!(font.header().vertical_layout(), VerticalLayout::SMUSH);
+    }
+
+    #[test]
+    fn old_layout_only() {
+        let font = font_bytes("flf2a$ 2 1 4 15 1\ncomment\n", " @\n @@\n", "a@\na@@\n");
+        let font = FIGfont::read_from(&font[..]).unwrap();
+        let header = font.header();
+
+        assert_eq!(
+            header.layout(),
+            Layout::from_bits_truncate(15) | Layout::HORIZONTAL_SMUSH
+        );
+        assert_eq!(header.default_layout_mode(), LayoutMode::Smushing);
+        assert!(matches!(
+            header.print_direction(),
+            PrintDirection::LeftToRight
+        ));
+        assert_eq!(header.codetag_count(), None);
+    }
+
+    #[test]
+    fn multibyte_hard_blank() {
+        let font = font_bytes("flf2a¤ 1 1 2 0 1\ncomment\n", "¤@@\n", "a@@\n");
+        let font = FIGfont::read_from(&font[..]).unwrap();
+
+        assert_eq!(font.header().hard_blank_char(), "¤".as_bytes());
+        assert_eq!(font.get(32).lines()[0], vec![SubCharacter::Blank]);
+    }
+
+    #[test]
+    fn baseline() {
+        let font = font_bytes("flf2a$ 1 2 2 0 1\ncomment\n", "a@@\n", "a@@\n");
+        assert!(matches!(
+            FIGfont::read_from(&font[..]),
+            Err(Error::Parse(ParseError::InvalidBaseline {
+                baseline: 2,
+                height: 1
+            }))
+        ));
+    }
+
+    #[test]
+    fn positional_header_fields() {
+        let header = |fields: &str| {
+            let header = format!("flf2a$ 1 1 2 15 1 {}\ncomment\n", fields);
+            FIGfont::read_from(&font_bytes(&header, "a@@\n", "a@@\n")[..])
+        };
+
+        assert!(header("0 143").is_ok());
+        assert!(header("1 143").is_ok());
+        assert!(matches!(
+            header("143"),
+            Err(Error::Parse(ParseError::InvalidHeader))
+        ));
+        assert!(matches!(
+            header("143 0"),
+            Err(Error::Parse(ParseError::InvalidHeader))
+        ));
+    }
+
+    #[test]
+    fn space_hard_blank() {
+        let font = font_bytes("flf2a  1 1 3 0 1\ncomment\n", " @@\n", "a a@@\n");
+        let font = FIGfont::read_from(&font[..]).unwrap();
+
+        assert_eq!(font.header().hard_blank_char(), b" ");
+        assert_eq!(font.header().height(), 1);
+        assert_eq!(font.get(32).lines()[0], vec![SubCharacter::from(' ')]);
+        assert!(!font.get('A' as i32).lines()[0].iter().any(|c| c.is_blank()));
+
+        let character = FIGcharacter::from_rows(&["a a"], b' ').unwrap();
+        assert!(!character.lines()[0].iter().any(|c| c.is_blank()));
+    }
+
+    #[test]
+    fn space() {
+        let header = "flf2a$ 2 1 4 0 1\ncomment\n";
+
+        let font = FIGfont::read_from(&font_bytes(header, "@\n@@\n", "a@\na@@\n")[..]).unwrap();
+        assert_eq!(font.get(32).height(), 2);
+        assert_eq!(font.get(32).width(), 0);
+
+        let font = FIGfont::read_from(&font_bytes(header, " $@\n $@@\n", "a@\na@@\n")[..]).unwrap();
+        assert_eq!(font.get(32).width(), 2);
+        assert_eq!(font.get(32).ink_bounds(true), None);
+    }
+
+    #[test]
+    fn pad_rows() {
+        let font = font_bytes("flf2a$ 2 1 4 0 1\ncomment\n", "a@\nab@@\n", "a@\na@@\n");
+        let padded = FIGfont::read_from(&font[..]).unwrap();
+        assert!(padded.get(32).is_rectangular());
+        assert_eq!(padded.get(32).lines()[0].len(), 2);
+
+        let options = ParseOptions::new().pad_rows(false);
+        let ragged = FIGfont::read_from_with_options(&font[..], &options).unwrap();
+        assert!(!ragged.get(32).is_rectangular());
+        assert!(ragged.get('A' as i32).is_rectangular());
+        assert_eq!(ragged.get(32).row_widths(), vec![1, 2]);
+        assert_eq!(padded.get(32).row_widths(), vec![2, 2]);
+    }
+
+    #[test]
+    fn limits() {
+        let limit = |options: &ParseOptions, font: &[u8]| match FIGfont::read_from_with_options(
+            font, options,
+        ) {
+            Err(Error::Parse(ParseError::LimitExceeded(limit))) => Some(limit),
+            _ => None,
+        };
+        let options = ParseOptions::new();
+
+        let font = font_bytes("flf2a$ 2 1 4 0 1\ncomment\n", " @\n @@\n", "ab@\nab@@\n");
+        assert_eq!(limit(&options, &font), None);
+        assert_eq!(limit(&options.clone().max_height(1), &font), Some("height"));
+        assert_eq!(limit(&options.clone().max_char_width(4), &font), None);
+        assert_eq!(
+            limit(&options.clone().max_char_width(3), &font),
+            Some("char width")
+        );
+        assert_eq!(
+            limit(&options.clone().max_comment_lines(0), &font),
+            Some("comment lines")
+        );
+
+        let huge = b"flf2a$ 4000000000 1 4 0 4000000000\n";
+        assert_eq!(limit(&options, huge), Some("comment lines"));
+
+        let long = "x".repeat(65537);
+        let comment = format!("flf2a$ 2 1 4 0 1\n{}\n", long);
+        let font = font_bytes(&comment, " @\n @@\n", "ab@\nab@@\n");
+        assert_eq!(limit(&options, &font), Some("line length"));
+        let header = format!("flf2a$ 2 1 4 0 1{}\ncomment\n", " ".repeat(65537));
+        let font = font_bytes(&header, " @\n @@\n", "ab@\nab@@\n");
+        assert_eq!(limit(&options, &font), Some("line length"));
+        let mut font = font_bytes("flf2a$ 2 1 4 0 1\ncomment\n", " @\n @@\n", "ab@\nab@@\n");
+        font.extend(format!("300 {}\nab@\nab@@\n", long).as_bytes());
+        assert_eq!(limit(&options, &font), Some("line length"));
+        assert_eq!(limit(&options.clone().max_line_length(66000), &font), None);
+    }
+
+    #[test]
+    fn single_row() {
+        let header = "flf2a$ 1 1 3 -1 1\ncomment\n";
+
+        for character in &["ab@\n", "ab@@\n"] {
+            let font = FIGfont::read_from(&font_bytes(header, " @\n", character)[..]).unwrap();
+            assert_eq!(font.get(32).lines()[..], [vec![SubCharacter::from(' ')]]);
+            assert_eq!(font.get('A' as i32).to_string(), "ab");
+        }
+
+        let term = FIGfont::load_from("fonts/plain/term.flf").unwrap();
+        assert_eq!(term.get('@' as i32).to_string(), "@");
+    }
+
+    #[test]
+    fn empty_rows() {
+        let header = "flf2a$ 2 1 4 0 1\ncomment\n";
+
+        let font = font_bytes(header, "@\n@@\n", "a@\na@@\n");
+        assert!(FIGfont::read_from(&font[..]).is_ok());
+
+        for space in &["\n@@\n", "@\n\n"] {
+            let font = font_bytes(header, space, "a@\na@@\n");
+            assert!(matches!(
+                FIGfont::read_from(&font[..]),
+                Err(Error::Parse(ParseError::InvalidCharacter))
+            ));
+        }
+    }
+
+    #[test]
+    fn height_override() {
+        let font = font_bytes("flf2a$ 3 1 4 0 1\ncomment\n", " @\n @@\n", " @\n @@\n");
+
+        assert!(FIGfont::read_from(&font[..]).is_err());
+
+        let options = ParseOptions::new().height_override(Some(2));
+        let font = FIGfont::read_from_with_options(&font[..], &options).unwrap();
+        assert_eq!(font.get('A' as i32).height(), 2);
+        assert!(!font.height_consistency());
+        assert_eq!(font.inconsistent_heights().len(), 102);
+        assert_eq!(font.inconsistent_heights()[0], 32);
+    }
+
+    #[test]
+    fn raw_fields() {
+        assert_eq!(
+            FIGfont::standard().unwrap().header().raw_fields(),
+            RawFields {
+                hard_blank: b"$".to_vec(),
+                height: 6,
+                baseline: 5,
+                max_length: 16,
+                old_layout: 15,
+                comment_lines: 11,
+                print_direction: Some(PrintDirection::LeftToRight),
+                full_layout: Some(Layout::from_bits(24463).unwrap()),
+                codetag_count: Some(229),
+            }
+        );
+
+        let raw = validate_header(&b"flf2a$ 1 1 2 -1 0\n"[..])
+            .unwrap()
+            .raw_fields();
+        assert_eq!(raw.old_layout, -1);
+        assert_eq!(raw.comment_lines, 0);
+        assert_eq!(raw.print_direction, None);
+        assert_eq!(raw.full_layout, None);
+        assert_eq!(raw.codetag_count, None);
+    }
+
+    #[test]
+    fn metrics() {
+        assert_eq!(
+            FIGfont::standard().unwrap().metrics(),
+            Metrics {
+                height: 6,
+                baseline: 5,
+                max_length: 16,
+            }
+        );
+    }
+
+    #[test]
+    fn actual_codetag_count() {
+        let font = FIGfont::standard().unwrap();
+        assert_eq!(font.actual_codetag_count(), 229);
+        assert_eq!(
+            Some(font.actual_codetag_count()),
+            font.header().codetag_count()
+        );
+    }
+
+    #[test]
+    fn suspicious_codetags() {
+        for entry in std::fs::read_dir("fonts/plain").unwrap() {
+            let path = entry.unwrap().path();
+            let font = FIGfont::load_from(&path).unwrap();
+            assert!(font.suspicious_codetags().is_empty(), "{}", path.display());
+        }
+
+        let mut font = font_bytes("flf2a$ 1 1 2 0 1\ncomment\n", "a@@\n", "a@@\n");
+        font.extend(b"300\nb@@\n200\nb@@\n0x110000\nb@@\n400\nb@@\n");
+        font.extend(b"-2\nb@@\n-5\nb@@\n-3\nb@@\n500\nb@@\n-6\nb@@\n");
+        let font = FIGfont::read_from(&font[..]).unwrap();
+        assert_eq!(font.suspicious_codetags(), vec![200, 0x110000, 400, -3]);
+    }
+
+    #[test]
+    fn content_hash() {
+        let font = FIGfont::standard().unwrap();
+        assert_eq!(
+            font.content_hash(),
+            FIGfont::standard().unwrap().content_hash()
+        );
+
+        let mut other = font.clone();
+        other
+            .set_char('A' as i32, font.get('B' as i32).clone())
+            .unwrap();
+        assert_ne!(font.content_hash(), other.content_hash());
+        assert_eq!(font.content_hash(), 0x59fd_de89_ab87_6ae8);
+    }
+
+    #[test]
+    fn zero_codetag() {
+        for codetag in ["0", "00", "0x0", "-0"] {
+            let mut font = font_bytes("flf2a$ 1 1 2 0 1\ncomment\n", "a@@\n", "a@@\n");
+            font.extend(format!("{} NUL\nb@@\n", codetag).as_bytes());
+            let font = FIGfont::read_from(&font[..]).unwrap();
+            assert_eq!(
+                font.get(0).comment().unwrap().as_str(),
+                "NUL",
+                "{}",
+                codetag
+            );
+        }
+    }
+
+    #[test]
+    fn chars_containing() {
+        let font = font_bytes("flf2a$ 1 1 3 0 1\ncomment\n", "$$@@\n", "a/@@\n");
+        let mut font = FIGfont::read_from(&font[..]).unwrap();
+        font.set_char('/' as i32, FIGcharacter::from_rows(&["//"], b'$').unwrap())
+            .unwrap();
+
+        assert_eq!(font.chars_containing(&SubCharacter::Blank), vec![32]);
+        assert_eq!(font.chars_containing(&SubCharacter::from('/')).len(), 101);
+        assert_eq!(font.chars_containing(&SubCharacter::from('a')).len(), 100);
+        assert!(font.chars_containing(&SubCharacter::from('x')).is_empty());
+    }
+
+    #[test]
+    fn progress() {
+        let mut calls = Vec::new();
+        FIGfont::read_from_with_progress(
+            crate::STANDARD_FONT,
+            &Default::default(),
+            |parsed, total| calls.push((parsed, total)),
+        )
+        .unwrap();
+
+        assert_eq!(calls.len(), 102 + 229);
+        assert_eq!(calls[0], (1, Some(331)));
+        assert_eq!(calls.last(), Some(&(331, Some(331))));
+    }
+
+    #[test]
+    fn codetag_overflow() {
+        let mut font = font_bytes("flf2a$ 1 1 2 0 1\ncomment\n", "a@@\n", "a@@\n");
+        font.extend(b"--2147483648\nb@@\n");
+        assert!(matches!(
+            FIGfont::read_from(&font[..]),
+            Err(Error::Parse(ParseError::InvalidCharacter))
+        ));
+    }
+
+    #[test]
+    fn art_encoding() {
+        assert_eq!(
+            FIGfont::standard().unwrap().art_encoding(),
+            ArtEncoding::Ascii
+        );
+
+        let font = font_bytes("flf2a$ 1 1 3 0 1\ncomment\n", "\u{2502}@@\n", "a@@\n");
+        assert_eq!(
+            FIGfont::read_from(&font[..]).unwrap().art_encoding(),
+            ArtEncoding::Utf8
+        );
+
+        let mut font = font_bytes("flf2a$ 1 1 3 0 1\ncomment\n", "a@@\n", "a@@\n");
+        font.extend(b"300\n\xA6@@\n");
+        assert_eq!(
+            FIGfont::read_from(&font[..]).unwrap().art_encoding(),
+            ArtEncoding::Latin1
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn wide_widths() {
+        let rows = r#"{"comment":null,"lines":[[{"Symbol":"\u4e2d"}],[{"Symbol":"a"},"Blank"]]}"#;
+        let character: FIGcharacter = serde_json::from_str(rows).unwrap();
+        assert_eq!(character.row_widths(), vec![2, 2]);
+        assert_eq!(character.width(), 2);
+        assert!(character.is_rectangular());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn art_encoding_past_latin1() {
+        let font = font_bytes("flf2a$ 1 1 2 0 1\ncomment\n", "a@@\n", "a@@\n");
+        let mut font = FIGfont::read_from(&font[..]).unwrap();
+        let character = r#"{"comment":null,"lines":[[{"Symbol":"\u0102"}]]}"#;
+        font.set_char('A' as i32, serde_json::from_str(character).unwrap())
+            .unwrap();
+        assert_eq!(font.art_encoding(), ArtEncoding::Utf8);
+    }
+
+    #[test]
+    fn char_set_diff() {
+        let standard = FIGfont::standard().unwrap();
+        let mut other = standard.clone();
+        other.characters.remove(&0x100);
+        other.characters.remove(&('A' as i32));
+        other
+            .set_char(-2, standard.get('A' as i32).clone())
+            .unwrap();
+
+        assert_eq!(
+            standard.char_set_diff(&other),
+            (vec!['A' as i32, 0x100], vec![-2])
+        );
+        assert_eq!(standard.char_set_diff(&standard), (vec![], vec![]));
+    }
+
+    #[test]
+    fn trailer() {
+        let options = ParseOptions::new().capture_trailer(true);
+
+        let mut font = crate::STANDARD_FONT.to_vec();
+        font.extend(b"signature\n");
+        assert!(FIGfont::read_from(&font[..]).is_err());
+        let font = FIGfont::read_from_with_options(&font[..], &options).unwrap();
+        assert_eq!(font.trailer(), Some(&b"signature\n"[..]));
+        assert_eq!(font.actual_codetag_count(), 229);
+
+        let mut font = font_bytes("flf2a$ 1 1 2 0 1\ncomment\n", "a@@\n", "a@@\n");
+        font.extend(b"300\nb@@\njunk");
+        let font = FIGfont::read_from_with_options(&font[..], &options).unwrap();
+        assert_eq!(font.trailer(), Some(&b"junk"[..]));
+        assert_eq!(font.actual_codetag_count(), 1);
+
+        let font = FIGfont::read_from_with_options(crate::STANDARD_FONT, &options).unwrap();
+        assert_eq!(font.trailer(), None);
+    }
+
+    #[test]
+    fn set_char() {
+        let mut font = FIGfont::standard().unwrap();
+        let a = font.get('A' as i32).clone();
+        assert!(font.set_char(0x100, a.clone()).is_ok());
+        assert_eq!(font.get(0x100).lines(), font.get('A' as i32).lines());
+        assert_eq!(font.actual_codetag_count(), 229);
+
+        assert!(font.set_char(0x2603, a.clone()).is_ok());
+        assert_eq!(font.actual_codetag_count(), 230);
+        assert_eq!(font.header().codetag_count(), Some(230));
+        assert!(font.set_char(0x110000, a).is_ok());
+        assert_eq!(font.suspicious_codetags(), vec![0x110000]);
+
+        let mut short = FIGfont::read_from(&include_bytes!("../fonts/plain/mini.flf")[..]).unwrap();
+        let a = short.get('A' as i32).clone();
+        assert!(font.set_char('A' as i32, a.clone()).is_err());
+        assert!(short.set_char('B' as i32, a).is_ok());
+    }
+
+    #[test]
+    fn is_blank() {
+        let font = FIGfont::standard().unwrap();
+        assert!(font.get(32).is_blank());
+        assert!(!font.get('A' as i32).is_blank());
+    }
+
+    #[test]
+    fn ink_count() {
+        let character = FIGcharacter::from_rows(&["/$ ", " \\$"], b'$').unwrap();
+        assert_eq!(character.ink_count(true), 2);
+        assert_eq!(character.ink_count(false), 4);
+        assert_eq!(FIGfont::standard().unwrap().get(32).ink_count(true), 0);
+    }
+
+    #[test]
+    fn font_builder() {
+        let standard = FIGfont::standard().unwrap();
+        let builder = REQUIRED_ASCII.fold(
+            FontBuilder::new(standard.header().clone()),
+            |builder, code| builder.character(code, standard.get(code).clone()),
+        );
+
+        let font = builder
+            .clone()
+            .character(0x100, standard.get(0x100).clone());
+        let font = font.build().unwrap();
+        assert_eq!(font.get('A' as i32), standard.get('A' as i32));
+        assert_eq!(font.actual_codetag_count(), 1);
+        assert_eq!(font.header().codetag_count(), Some(1));
+
+        let a = FIGcharacter::from_rows(&["a"], b'$').unwrap();
+        assert!(matches!(
+            builder.clone().character('A' as i32, a).build(),
+            Err(Error::HeightMismatch {
+                expected: 6,
+                found: 1
+            })
+        ));
+
+        let incomplete = FontBuilder::new(standard.header().clone())
+            .character('~' as i32, standard.get('~' as i32).clone());
+        assert!(matches!(
+            incomplete.clone().build(),
+            Err(Error::MissingCharacters(missing)) if missing.len() == 94
+        ));
+        assert!(incomplete.allow_incomplete(true).build().is_ok());
+
+        let layout = Layout::HORIZONTAL_SMUSH | Layout::HORIZONTAL_EQUAL;
+        let header = Header::new(b"$", 1, 1, 2, layout)
+            .unwrap()
+            .with_comment("made\nfrom scratch");
+        let raw = header.raw_fields();
+        assert_eq!((raw.old_layout, raw.comment_lines), (1, 2));
+        let a = FIGcharacter::from_rows(&["a"], b'$').unwrap();
+        let font = REQUIRED_ASCII
+            .fold(FontBuilder::new(header), |builder, code| {
+                builder.character(code, a.clone())
+            })
+            .build()
+            .unwrap();
+        assert_eq!(font.header().codetag_count(), Some(0));
+        assert_eq!(font.header().comment(), "made\nfrom scratch");
+
+        assert!(Header::new(b"", 1, 1, 2, layout).is_err());
+        assert!(Header::new(b"a b", 1, 1, 2, layout).is_err());
+        assert!(Header::new(b"$\r", 1, 1, 2, layout).is_err());
+        assert!(Header::new(b"$", 0, 0, 2, layout).is_err());
+        assert!(Header::new(b"$", 1, 2, 2, layout).is_err());
+    }
+
+    #[test]
+    fn from_rows() {
+        let character = FIGcharacter::from_rows(&["/\\", "\\$"], b'$').unwrap();
+        assert_eq!(character.height(), 2);
+        assert_eq!(character.width(), 2);
+        assert_eq!(character.lines()[1][1], SubCharacter::Blank);
+
+        assert!(FIGcharacter::from_rows(&["_", "| |"], b'$').is_err());
+        assert!(FIGcharacter::from_rows(&[], b'$').is_err());
+
+        let font = font_bytes("flf2a$ 1 1 5 0 1\ncomment\n", "\u{2502}$@@\n", "a@@\n");
+        let font = FIGfont::read_from(&font[..]).unwrap();
+        let character = FIGcharacter::from_rows(&["\u{2502}$"], b'$').unwrap();
+        assert_eq!(&character, font.get(32));
+        assert_eq!(character.lines()[0].len(), 4);
+    }
+
+    #[test]
+    fn visible_width() {
+        let character = FIGcharacter::from_rows(&["a   ", "b$  "], b'$').unwrap();
+        assert_eq!(character.width(), 4);
+        assert_eq!(character.visible_width(), 2);
+    }
+
+    #[test]
+    fn left_trimmed() {
+        let character = FIGcharacter::from_rows(&["   _ ", "     ", "  |$|"], b'$').unwrap();
+        assert_eq!(character.left_trimmed().to_string(), " _ \n   \n| |");
+
+        let font = FIGfont::standard().unwrap();
+        assert_eq!(font.get(32).left_trimmed().width(), 1);
+    }
+
+    #[test]
+    fn try_width() {
+        let font = font_bytes("flf2a$ 1 1 2 0 1\ncomment\n", "@@\n", "a@@\n");
+        let font = FIGfont::read_from(&font[..]).unwrap();
+        assert_eq!(font.get(32).try_width(), Some(0));
+        assert_eq!(font.get('A' as i32).try_width(), Some(1));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn try_width_empty() {
+        let empty: FIGcharacter = serde_json::from_str(r#"{"comment":null,"lines":[]}"#).unwrap();
+        assert_eq!(empty.try_width(), None);
+        assert_eq!(empty.width(), 0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let font = FIGfont::standard().unwrap();
+        let json = serde_json::to_string(&font).unwrap();
+        assert_eq!(serde_json::from_str::<FIGfont>(&json).unwrap(), font);
+
+        assert!(serde_json::from_str::<Layout>("1048576").is_err());
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn parse_stats() {
+        let font = font_bytes("flf2a$ 1 1 2 0 1\ncomment\n", " @@\n", "a@@\n");
+        let (_, stats) = FIGfont::read_from_with_stats(&font[..], &Default::default()).unwrap();
+
+        assert_eq!(stats.header.bytes, "flf2a$ 1 1 2 0 1\n".len());
+        assert_eq!(stats.comment.bytes, "comment\n".len());
+        assert_eq!(stats.characters.bytes, " @@\n".len() + 101 * "a@@\n".len());
+        assert_eq!(stats.codetags.bytes, 0);
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn tiny_font() {
+        assert_eq!(crate::testing::tiny_font().header().height(), 4);
+    }
 }
@@ -0,0 +1,82 @@
+/// Options controlling how a FIGfont is parsed.
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    pub(crate) height_override: Option<usize>,
+    pub(crate) capture_trailer: bool,
+    pub(crate) pad_rows: bool,
+    pub(crate) max_height: usize,
+    pub(crate) max_comment_lines: usize,
+    pub(crate) max_char_width: usize,
+    pub(crate) max_line_length: usize,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            height_override: None,
+            capture_trailer: false,
+            pad_rows: true,
+            max_height: 1024,
+            max_comment_lines: 65536,
+            max_char_width: 4096,
+            max_line_length: 65536,
+        }
+    }
+}
+
+impl ParseOptions {
+    /// Create the default parse options.
+    pub fn new() -> ParseOptions {
+        Default::default()
+    }
+
+    /// Read every FIGcharacter as `height` lines tall, ignoring the height
+    /// declared in the header. Useful to recover fonts with a corrupted header.
+    pub fn height_override(mut self, height: Option<usize>) -> ParseOptions {
+        self.height_override = height;
+        self
+    }
+
+    /// Keep the data found after the last FIGcharacter instead of failing on
+    /// it, see FIGfont::trailer. Parsing stops at the first data that isn't a
+    /// codetagged FIGcharacter, or after the codetag count declared in the
+    /// header.
+    pub fn capture_trailer(mut self, capture: bool) -> ParseOptions {
+        self.capture_trailer = capture;
+        self
+    }
+
+    /// Right-pad every row of a FIGcharacter with spaces to the width of its
+    /// widest row. Enabled by default.
+    pub fn pad_rows(mut self, pad: bool) -> ParseOptions {
+        self.pad_rows = pad;
+        self
+    }
+
+    /// Fail on FIGcharacters taller than `height` lines. Defaults to 1024.
+    pub fn max_height(mut self, height: usize) -> ParseOptions {
+        self.max_height = height;
+        self
+    }
+
+    /// Fail on headers declaring more than `lines` comment lines. Defaults to
+    /// 65536.
+    pub fn max_comment_lines(mut self, lines: usize) -> ParseOptions {
+        self.max_comment_lines = lines;
+        self
+    }
+
+    /// Fail on FIGcharacter lines longer than `width` bytes, delimiters
+    /// included. Longer lines are never read in full. Defaults to 4096.
+    pub fn max_char_width(mut self, width: usize) -> ParseOptions {
+        self.max_char_width = width;
+        self
+    }
+
+    /// Fail on header, comment or codetag lines longer than `length` bytes.
+    /// Longer lines are never read in full. Defaults to 65536.
+    pub fn max_line_length(mut self, length: usize) -> ParseOptions {
+        self.max_line_length = length;
+        self
+    }
+}
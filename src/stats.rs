@@ -0,0 +1,116 @@
+use std::io::Read;
+
+#[cfg(feature = "metrics")]
+use std::{
+    io::BufReader,
+    time::{Duration, Instant},
+};
+
+/// How long a parse phase took and how many bytes it consumed.
+#[cfg(feature = "metrics")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PhaseStats {
+    pub duration: Duration,
+    pub bytes: usize,
+}
+
+/// Per-phase instrumentation of a FIGfont parse.
+#[cfg(feature = "metrics")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParseStats {
+    /// The header line.
+    pub header: PhaseStats,
+    /// The comment lines.
+    pub comment: PhaseStats,
+    /// The required ASCII and German FIGcharacters.
+    pub characters: PhaseStats,
+    /// The codetagged FIGcharacters, and the trailer if it's captured.
+    pub codetags: PhaseStats,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Phase {
+    Header,
+    Comment,
+    Characters,
+    Codetags,
+}
+
+/// Reader counting the bytes read through it.
+#[cfg(feature = "metrics")]
+pub(crate) struct CountingReader<R> {
+    inner: R,
+    count: usize,
+}
+
+#[cfg(feature = "metrics")]
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.count += read;
+        Ok(read)
+    }
+}
+
+/// Records the parse phases as they end.
+#[cfg(feature = "metrics")]
+pub(crate) struct Recorder {
+    stats: ParseStats,
+    last: Instant,
+    consumed: usize,
+}
+
+#[cfg(feature = "metrics")]
+impl Recorder {
+    pub(crate) fn new() -> Recorder {
+        Recorder {
+            stats: Default::default(),
+            last: Instant::now(),
+            consumed: 0,
+        }
+    }
+
+    pub(crate) fn wrap<R: Read>(reader: R) -> CountingReader<R> {
+        CountingReader {
+            inner: reader,
+            count: 0,
+        }
+    }
+
+    pub(crate) fn record<R>(&mut self, bread: &BufReader<CountingReader<R>>, phase: Phase) {
+        let now = Instant::now();
+        let consumed = bread.get_ref().count - bread.buffer().len();
+        let stats = match phase {
+            Phase::Header => &mut self.stats.header,
+            Phase::Comment => &mut self.stats.comment,
+            Phase::Characters => &mut self.stats.characters,
+            Phase::Codetags => &mut self.stats.codetags,
+        };
+
+        stats.duration = now - self.last;
+        stats.bytes = consumed - self.consumed;
+        self.last = now;
+        self.consumed = consumed;
+    }
+
+    pub(crate) fn finish(self) -> ParseStats {
+        self.stats
+    }
+}
+
+/// Without the metrics feature nothing is recorded.
+#[cfg(not(feature = "metrics"))]
+pub(crate) struct Recorder;
+
+#[cfg(not(feature = "metrics"))]
+impl Recorder {
+    pub(crate) fn new() -> Recorder {
+        Recorder
+    }
+
+    pub(crate) fn wrap<R: Read>(reader: R) -> R {
+        reader
+    }
+
+    pub(crate) fn record<T>(&mut self, _: &T, _: Phase) {}
+}
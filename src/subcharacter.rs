@@ -9,7 +9,8 @@ use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
 /// A SubCharacter is a single real character
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SubCharacter {
     /// The actual subcharacter
     Symbol(String),
@@ -29,7 +30,7 @@ impl<'a, 'b> Iterator for SplitWith<'a, 'b> {
 
     fn next(&mut self) -> Option<<Self as Iterator>::Item> {
         match self.haystack {
-            Some(haystack) => {
+            Some(haystack) if !self.when.is_empty() => {
                 for i in 0usize..haystack.len() {
                     if haystack[i..].starts_with(self.when) {
                         let res = &haystack[..i];
@@ -38,11 +39,13 @@ impl<'a, 'b> Iterator for SplitWith<'a, 'b> {
                     }
                 }
 
-                let res = Some(haystack);
                 self.haystack = None;
-                res
+                Some(haystack)
+            }
+            haystack => {
+                self.haystack = None;
+                haystack
             }
-            None => None,
         }
     }
 }
@@ -55,7 +58,8 @@ fn split<'a, 'b>(haystack: &'a [u8], when: &'b [u8]) -> SplitWith<'a, 'b> {
 }
 
 impl SubCharacter {
-    /// Split a Latin1-encoded string in a Vec<SubCharacter>
+    /// Split a Latin1-encoded string in a Vec<SubCharacter>. An empty
+    /// `blank_character` means there are no hard blanks.
     pub fn split(raw: &[u8], blank_character: &[u8]) -> Result<Vec<SubCharacter>, String> {
         let mut res = Vec::new();
         for (i, string) in split(raw, blank_character).enumerate() {
@@ -0,0 +1,8 @@
+use crate::FIGfont;
+
+const TINY_FONT: &[u8] = include_bytes!("../fonts/plain/mini.flf");
+
+/// Get a small, known-good FIGfont (mini.flf, 4 lines tall).
+pub fn tiny_font() -> FIGfont {
+    FIGfont::read_from(TINY_FONT).expect("the bundled tiny font is valid")
+}
@@ -2,9 +2,23 @@ use crate::error::ParseError;
 use crate::result::Result;
 use std::io::{BufRead, BufReader, Read};
 
-pub(crate) fn read_line<R: Read>(bread: &mut BufReader<R>) -> Result<Vec<u8>> {
-    let mut line = Vec::new();
-    bread.read_until(b'\n', &mut line)?;
+pub(crate) const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+pub(crate) fn skip_bom_and_whitespace(line: &[u8]) -> &[u8] {
+    let line = line.strip_prefix(BOM).unwrap_or(line);
+    let start = line
+        .iter()
+        .position(|c| !c.is_ascii_whitespace())
+        .unwrap_or(line.len());
+    &line[start..]
+}
+
+pub(crate) fn read_line_limited<R: Read>(
+    bread: &mut BufReader<R>,
+    max_len: usize,
+    limit: &'static str,
+) -> Result<Vec<u8>> {
+    let mut line = read_raw_line(bread, max_len, limit)?;
 
     if line.ends_with(b"\r\n") {
         line.truncate(line.len() - 2);
@@ -17,9 +31,12 @@ pub(crate) fn read_line<R: Read>(bread: &mut BufReader<R>) -> Result<Vec<u8>> {
     Ok(line)
 }
 
-pub(crate) fn read_last_line<R: Read>(bread: &mut BufReader<R>) -> Result<Vec<u8>> {
-    let mut line = Vec::new();
-    bread.read_until(b'\n', &mut line)?;
+pub(crate) fn read_last_line<R: Read>(
+    bread: &mut BufReader<R>,
+    max_len: usize,
+    limit: &'static str,
+) -> Result<Vec<u8>> {
+    let mut line = read_raw_line(bread, max_len, limit)?;
 
     if line.ends_with(b"\r\n") {
         line.truncate(line.len() - 2);
@@ -29,3 +46,63 @@ pub(crate) fn read_last_line<R: Read>(bread: &mut BufReader<R>) -> Result<Vec<u8
 
     Ok(line)
 }
+
+/// Read a line, line ending included, without reading more than `max_len`
+/// bytes plus the line ending. A longer line exceeds the `limit` parse
+/// limit.
+pub(crate) fn read_raw_line<R: Read>(
+    bread: &mut BufReader<R>,
+    max_len: usize,
+    limit: &'static str,
+) -> Result<Vec<u8>> {
+    let mut line = Vec::new();
+    bread
+        .by_ref()
+        .take((max_len as u64).saturating_add(2))
+        .read_until(b'\n', &mut line)?;
+
+    let len = if line.ends_with(b"\r\n") {
+        line.len() - 2
+    } else if line.ends_with(b"\n") {
+        line.len() - 1
+    } else {
+        line.len()
+    };
+
+    if len > max_len {
+        return Err(ParseError::LimitExceeded(limit).into());
+    }
+
+    Ok(line)
+}
+
+/// 64-bit FNV-1a, a fixed hash whose values can be persisted.
+pub(crate) struct Fnv1a(u64);
+
+impl Fnv1a {
+    pub(crate) fn new() -> Fnv1a {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+
+    pub(crate) fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    pub(crate) fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    /// Write `bytes` after their length, so consecutive byte strings can't
+    /// run into each other.
+    pub(crate) fn write_bytes(&mut self, bytes: &[u8]) {
+        self.write_u64(bytes.len() as u64);
+        self.write(bytes);
+    }
+
+    pub(crate) fn finish(&self) -> u64 {
+        self.0
+    }
+}